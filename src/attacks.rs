@@ -0,0 +1,175 @@
+use crate::bitboard::Bitboard;
+use crate::game::Color;
+
+// Precomputed attack tables, built at compile time so they cost nothing at startup.
+// Every table is indexed by square (a1 = 0, h8 = 63).
+
+const KNIGHT_STEPS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_STEPS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+const WHITE_PAWN_STEPS: [(i32, i32); 2] = [(-1, 1), (1, 1)];
+const BLACK_PAWN_STEPS: [(i32, i32); 2] = [(-1, -1), (1, -1)];
+
+// The eight directions a slider moves in, as seen by white
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    Northwest,
+    Northeast,
+    Southwest,
+    Southeast,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North, Direction::South, Direction::East, Direction::West,
+        Direction::Northwest, Direction::Northeast, Direction::Southwest, Direction::Southeast,
+    ];
+
+    // (files, ranks) moved by one step
+    pub const fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::South => (0, -1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::Northwest => (-1, 1),
+            Direction::Northeast => (1, 1),
+            Direction::Southwest => (-1, -1),
+            Direction::Southeast => (1, -1),
+        }
+    }
+}
+
+pub static KNIGHT_ATTACKS: [Bitboard; 64] = step_attacks(&KNIGHT_STEPS);
+pub static KING_ATTACKS: [Bitboard; 64] = step_attacks(&KING_STEPS);
+// Diagonal captures only, indexed by Color::index first
+pub static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [step_attacks(&WHITE_PAWN_STEPS), step_attacks(&BLACK_PAWN_STEPS)];
+// The square in front of a pawn, indexed by Color::index first
+pub static PAWN_PUSHES: [[Bitboard; 64]; 2] = [step_attacks(&[(0, 1)]), step_attacks(&[(0, -1)])];
+// The square two in front of a pawn on its starting rank, empty everywhere else
+pub static PAWN_DOUBLE_PUSHES: [[Bitboard; 64]; 2] = [double_pushes(1, 1), double_pushes(6, -1)];
+// Every square up to the board edge in one direction, indexed by Direction first
+pub static RAYS: [[Bitboard; 64]; 8] = ray_tables();
+
+pub fn knight_attacks(square: usize) -> Bitboard {
+    KNIGHT_ATTACKS[square]
+}
+
+pub fn king_attacks(square: usize) -> Bitboard {
+    KING_ATTACKS[square]
+}
+
+// Squares a pawn of `color` standing on `square` attacks diagonally
+pub fn pawn_attacks(square: usize, color: Color) -> Bitboard {
+    PAWN_ATTACKS[color as usize][square]
+}
+
+pub fn pawn_pushes(square: usize, color: Color) -> Bitboard {
+    PAWN_PUSHES[color as usize][square]
+}
+
+pub fn pawn_double_pushes(square: usize, color: Color) -> Bitboard {
+    PAWN_DOUBLE_PUSHES[color as usize][square]
+}
+
+pub fn ray(direction: Direction, square: usize) -> Bitboard {
+    RAYS[direction as usize][square]
+}
+
+// The square `files` and `ranks` away from `square`, if it is on the board
+const fn step(square: usize, files: i32, ranks: i32) -> Option<usize> {
+    let file = (square % 8) as i32 + files;
+    let rank = (square / 8) as i32 + ranks;
+    if file < 0 || file >= 8 || rank < 0 || rank >= 8 {
+        None
+    } else {
+        Some((rank * 8 + file) as usize)
+    }
+}
+
+// For each square, the squares one of the given steps away
+const fn step_attacks(steps: &[(i32, i32)]) -> [Bitboard; 64] {
+    let mut table = [Bitboard(0); 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            if let Some(target) = step(square, steps[i].0, steps[i].1) {
+                table[square].0 |= 1 << target;
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+const fn double_pushes(start_rank: usize, ranks: i32) -> [Bitboard; 64] {
+    let mut table = [Bitboard(0); 64];
+    let mut square = start_rank * 8;
+    while square < start_rank * 8 + 8 {
+        if let Some(target) = step(square, 0, 2 * ranks) {
+            table[square].0 = 1 << target;
+        }
+        square += 1;
+    }
+    table
+}
+
+const fn ray_tables() -> [[Bitboard; 64]; 8] {
+    let mut tables = [[Bitboard(0); 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let (files, ranks) = Direction::ALL[direction].offset();
+        let mut square = 0;
+        while square < 64 {
+            let mut current = square;
+            while let Some(next) = step(current, files, ranks) {
+                tables[direction][square].0 |= 1 << next;
+                current = next;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::Square;
+
+    #[test]
+    fn step_tables() {
+        assert_eq!(knight_attacks(Square::A1.index()), Square::B3.bitboard() | Square::C2.bitboard());
+        assert_eq!(knight_attacks(Square::E4.index()).popcount(), 8);
+        assert_eq!(king_attacks(Square::H8.index()), Square::G8.bitboard() | Square::G7.bitboard() | Square::H7.bitboard());
+        assert_eq!(king_attacks(Square::E4.index()).popcount(), 8);
+        assert_eq!(pawn_attacks(Square::E4.index(), Color::White), Square::D5.bitboard() | Square::F5.bitboard());
+        assert_eq!(pawn_attacks(Square::E4.index(), Color::Black), Square::D3.bitboard() | Square::F3.bitboard());
+        assert_eq!(pawn_attacks(Square::A2.index(), Color::White), Square::B3.bitboard());
+    }
+
+    #[test]
+    fn rays_run_to_the_edge() {
+        assert_eq!(ray(Direction::North, Square::A1.index()), Bitboard::FILE_A ^ Square::A1.bitboard());
+        assert_eq!(ray(Direction::West, Square::H1.index()), Bitboard::RANK_1 ^ Square::H1.bitboard());
+        assert_eq!(ray(Direction::Northeast, Square::A1.index()), Bitboard::DIAGONALS[7] ^ Square::A1.bitboard());
+        assert_eq!(ray(Direction::Southeast, Square::H4.index()), Bitboard::EMPTY);
+        for direction in Direction::ALL {
+            assert!(!ray(direction, Square::E4.index()).is_empty());
+        }
+    }
+
+    // Tables are usable in constant expressions
+    const E4_KNIGHT: Bitboard = KNIGHT_ATTACKS[28];
+
+    #[test]
+    fn tables_are_compile_time_constants() {
+        assert_eq!(E4_KNIGHT, knight_attacks(28));
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr};
+
+use crate::square::Square;
+use crate::utils::*;
+
+// A set of squares, one bit per square with a1 as the lowest bit and h8 as the highest
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Bitboard(pub u64);
+
+// Builds the masks of the 15 diagonals (`anti` false) or anti-diagonals (`anti` true).
+// A square belongs to diagonal 7 + file - rank and to anti-diagonal file + rank.
+const fn diagonal_masks(anti: bool) -> [Bitboard; 15] {
+    let mut masks = [Bitboard(0); 15];
+    let mut square = 0;
+    while square < 64 {
+        let (file, rank) = (square % 8, square / 8);
+        let index = if anti { file + rank } else { 7 + file - rank };
+        masks[index].0 |= 1 << square;
+        square += 1;
+    }
+    masks
+}
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(u64::MAX);
+
+    pub const FILE_A: Bitboard = Bitboard(0x0101_0101_0101_0101);
+    pub const FILE_B: Bitboard = Bitboard(Bitboard::FILE_A.0 << 1);
+    pub const FILE_C: Bitboard = Bitboard(Bitboard::FILE_A.0 << 2);
+    pub const FILE_D: Bitboard = Bitboard(Bitboard::FILE_A.0 << 3);
+    pub const FILE_E: Bitboard = Bitboard(Bitboard::FILE_A.0 << 4);
+    pub const FILE_F: Bitboard = Bitboard(Bitboard::FILE_A.0 << 5);
+    pub const FILE_G: Bitboard = Bitboard(Bitboard::FILE_A.0 << 6);
+    pub const FILE_H: Bitboard = Bitboard(Bitboard::FILE_A.0 << 7);
+
+    pub const RANK_1: Bitboard = Bitboard(0xFF);
+    pub const RANK_2: Bitboard = Bitboard(Bitboard::RANK_1.0 << 8);
+    pub const RANK_3: Bitboard = Bitboard(Bitboard::RANK_1.0 << 16);
+    pub const RANK_4: Bitboard = Bitboard(Bitboard::RANK_1.0 << 24);
+    pub const RANK_5: Bitboard = Bitboard(Bitboard::RANK_1.0 << 32);
+    pub const RANK_6: Bitboard = Bitboard(Bitboard::RANK_1.0 << 40);
+    pub const RANK_7: Bitboard = Bitboard(Bitboard::RANK_1.0 << 48);
+    pub const RANK_8: Bitboard = Bitboard(Bitboard::RANK_1.0 << 56);
+
+    // Indexed by File::index and Rank::index
+    pub const FILES: [Bitboard; 8] = [
+        Bitboard::FILE_A, Bitboard::FILE_B, Bitboard::FILE_C, Bitboard::FILE_D,
+        Bitboard::FILE_E, Bitboard::FILE_F, Bitboard::FILE_G, Bitboard::FILE_H,
+    ];
+    pub const RANKS: [Bitboard; 8] = [
+        Bitboard::RANK_1, Bitboard::RANK_2, Bitboard::RANK_3, Bitboard::RANK_4,
+        Bitboard::RANK_5, Bitboard::RANK_6, Bitboard::RANK_7, Bitboard::RANK_8,
+    ];
+
+    // Diagonals run towards h8 and are indexed by 7 + file - rank, so a1-h8 is DIAGONALS[7].
+    // Anti-diagonals run towards a8 and are indexed by file + rank, so h1-a8 is ANTI_DIAGONALS[7].
+    pub const DIAGONALS: [Bitboard; 15] = diagonal_masks(false);
+    pub const ANTI_DIAGONALS: [Bitboard; 15] = diagonal_masks(true);
+
+    // The bitboard holding only the square with the given index
+    pub fn from_index(index: usize) -> Bitboard {
+        Bitboard(1 << index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, square: Square) -> bool {
+        !(*self & square.bitboard()).is_empty()
+    }
+
+    pub fn popcount(&self) -> u32 {
+        popcount(self.0)
+    }
+
+    // The lowest set square, None when empty
+    pub fn lsb(&self) -> Option<Square> {
+        bit_scan(self.0).and_then(Square::from_index)
+    }
+
+    // The highest set square, None when empty
+    pub fn msb(&self) -> Option<Square> {
+        bit_scan_backwards(self.0).and_then(Square::from_index)
+    }
+
+    // Clears the lowest set square and returns it
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    // The set squares from a1 towards h8
+    pub fn squares(self) -> Squares {
+        Squares(self)
+    }
+
+    // Every square moved one step in a direction as seen by white. Squares stepping
+    // off the board are dropped instead of wrapping around to the opposite file.
+    pub fn north(self) -> Bitboard {
+        self << 8
+    }
+
+    pub fn south(self) -> Bitboard {
+        self >> 8
+    }
+
+    pub fn east(self) -> Bitboard {
+        (self & !Bitboard::FILE_H) << 1
+    }
+
+    pub fn west(self) -> Bitboard {
+        (self & !Bitboard::FILE_A) >> 1
+    }
+
+    pub fn north_east(self) -> Bitboard {
+        (self & !Bitboard::FILE_H) << 9
+    }
+
+    pub fn north_west(self) -> Bitboard {
+        (self & !Bitboard::FILE_A) << 7
+    }
+
+    pub fn south_east(self) -> Bitboard {
+        (self & !Bitboard::FILE_H) >> 7
+    }
+
+    pub fn south_west(self) -> Bitboard {
+        (self & !Bitboard::FILE_A) >> 9
+    }
+}
+
+// Iterator over the squares of a bitboard, lowest first
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        self.0.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.popcount() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Squares {
+        self.squares()
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(squares: I) -> Bitboard {
+        squares.into_iter().fold(Bitboard::EMPTY, |bitboard, square| bitboard | square.bitboard())
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Bitboard) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Bitboard) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Bitboard) {
+        self.0 ^= rhs.0;
+    }
+}
+
+// Raw shifts move bits across the whole board and wrap between files,
+// the directional shifts above are the safe way to step squares
+impl Shl<usize> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, rhs: usize) -> Bitboard {
+        Bitboard(self.0 << rhs)
+    }
+}
+
+impl Shr<usize> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, rhs: usize) -> Bitboard {
+        Bitboard(self.0 >> rhs)
+    }
+}
+
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", bitboard_to_string(*self, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::{File, Rank};
+
+    #[test]
+    fn scans_and_pops_squares() {
+        let mut bitboard: Bitboard = [Square::B2, Square::E4, Square::H8].into_iter().collect();
+        assert_eq!(bitboard.popcount(), 3);
+        assert_eq!(bitboard.lsb(), Some(Square::B2));
+        assert_eq!(bitboard.msb(), Some(Square::H8));
+        assert_eq!(bitboard.squares().collect::<Vec<_>>(), vec![Square::B2, Square::E4, Square::H8]);
+
+        assert_eq!(bitboard.pop_lsb(), Some(Square::B2));
+        assert_eq!(bitboard.pop_lsb(), Some(Square::E4));
+        assert_eq!(bitboard.pop_lsb(), Some(Square::H8));
+        assert_eq!(bitboard.pop_lsb(), None);
+        assert!(bitboard.is_empty());
+        assert_eq!(Bitboard::EMPTY.lsb(), None);
+        assert_eq!(Bitboard::EMPTY.msb(), None);
+    }
+
+    #[test]
+    fn shifts_do_not_wrap_around_files() {
+        assert_eq!(Bitboard::FILE_H.east(), Bitboard::EMPTY);
+        assert_eq!(Bitboard::FILE_A.west(), Bitboard::EMPTY);
+        assert_eq!(Bitboard::RANK_8.north(), Bitboard::EMPTY);
+        assert_eq!(Bitboard::RANK_1.south(), Bitboard::EMPTY);
+        assert_eq!(Bitboard::FILE_A.east(), Bitboard::FILE_B);
+        assert_eq!(Square::H4.bitboard().north_east(), Bitboard::EMPTY);
+        assert_eq!(Square::A4.bitboard().north_west(), Bitboard::EMPTY);
+        assert_eq!(Square::H4.bitboard().south_east(), Bitboard::EMPTY);
+        assert_eq!(Square::A4.bitboard().south_west(), Bitboard::EMPTY);
+        assert_eq!(Square::E4.bitboard().north_east(), Square::F5.bitboard());
+        assert_eq!(Square::E4.bitboard().south_west(), Square::D3.bitboard());
+    }
+
+    #[test]
+    fn masks_match_squares() {
+        for square in Square::ALL {
+            let (file, rank) = (square.file().index(), square.rank().index());
+            assert!(Bitboard::FILES[file].contains(square));
+            assert!(Bitboard::RANKS[rank].contains(square));
+            assert!(Bitboard::DIAGONALS[7 + file - rank].contains(square));
+            assert!(Bitboard::ANTI_DIAGONALS[file + rank].contains(square));
+        }
+        assert_eq!(Bitboard::FILES[File::C.index()], File::C.bitboard());
+        assert_eq!(Bitboard::RANKS[Rank::Sixth.index()], Rank::Sixth.bitboard());
+        assert_eq!(Bitboard::DIAGONALS[7].popcount(), 8);
+        assert!(Bitboard::DIAGONALS[7].contains(Square::H8));
+        assert!(Bitboard::ANTI_DIAGONALS[7].contains(Square::A8));
+        assert_eq!(Bitboard::DIAGONALS[0], Square::A8.bitboard());
+        assert_eq!(Bitboard::DIAGONALS[14], Square::H1.bitboard());
+        assert_eq!(Bitboard::ANTI_DIAGONALS[14], Square::H8.bitboard());
+    }
+
+    #[test]
+    fn displays_as_board() {
+        assert_eq!(Bitboard::RANK_1.to_string(), bitboard_to_string(Bitboard::RANK_1, None));
+        assert!(Square::A8.bitboard().to_string().starts_with("1.......\n"));
+    }
+}
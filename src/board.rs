@@ -0,0 +1,102 @@
+use crate::bitboard::Bitboard;
+use crate::game::{Color, PieceType};
+
+// Piece placement kept two ways at once: a mailbox answering "what is on this square"
+// and bitboards answering "where are these pieces". Every change goes through
+// put_piece/remove_piece/move_piece so the two never disagree.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Board {
+    mailbox: [Option<(Color, PieceType)>; 64],
+    // Indexed by Color::index and PieceType::index
+    pieces: [[Bitboard; 6]; 2],
+    colors: [Bitboard; 2],
+    occupancy: Bitboard,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::empty()
+    }
+}
+
+impl Board {
+    pub fn empty() -> Board {
+        Board {
+            mailbox: [None; 64],
+            pieces: [[Bitboard::EMPTY; 6]; 2],
+            colors: [Bitboard::EMPTY; 2],
+            occupancy: Bitboard::EMPTY,
+        }
+    }
+
+    pub fn piece_at(&self, square: usize) -> Option<(Color, PieceType)> {
+        self.mailbox[square]
+    }
+
+    pub fn pieces(&self, color: Color, piece_type: PieceType) -> Bitboard {
+        self.pieces[color.index()][piece_type.index()]
+    }
+
+    pub fn color_occupancy(&self, color: Color) -> Bitboard {
+        self.colors[color.index()]
+    }
+
+    pub fn occupancy(&self) -> Bitboard {
+        self.occupancy
+    }
+
+    // Places a piece on an empty square
+    pub fn put_piece(&mut self, square: usize, color: Color, piece_type: PieceType) {
+        debug_assert!(self.mailbox[square].is_none(), "square {} is occupied", square);
+        let bit = Bitboard::from_index(square);
+        self.mailbox[square] = Some((color, piece_type));
+        self.pieces[color.index()][piece_type.index()] |= bit;
+        self.colors[color.index()] |= bit;
+        self.occupancy |= bit;
+    }
+
+    // Clears `square`, returning the piece that stood there
+    pub fn remove_piece(&mut self, square: usize) -> Option<(Color, PieceType)> {
+        let (color, piece_type) = self.mailbox[square]?;
+        let bit = Bitboard::from_index(square);
+        self.mailbox[square] = None;
+        self.pieces[color.index()][piece_type.index()] &= !bit;
+        self.colors[color.index()] &= !bit;
+        self.occupancy &= !bit;
+        Some((color, piece_type))
+    }
+
+    // Moves whatever stands on `from` to the empty square `to`
+    pub fn move_piece(&mut self, from: usize, to: usize) {
+        if let Some((color, piece_type)) = self.remove_piece(from) {
+            self.put_piece(to, color, piece_type);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mailbox_and_bitboards_stay_in_sync() {
+        let mut board = Board::empty();
+        board.put_piece(4, Color::White, PieceType::King);
+        board.put_piece(60, Color::Black, PieceType::King);
+        board.put_piece(12, Color::White, PieceType::Pawn);
+
+        assert_eq!(board.piece_at(12), Some((Color::White, PieceType::Pawn)));
+        assert_eq!(board.pieces(Color::White, PieceType::Pawn), Bitboard(1 << 12));
+        assert_eq!(board.color_occupancy(Color::White), Bitboard(1 << 4 | 1 << 12));
+        assert_eq!(board.occupancy(), Bitboard(1 << 4 | 1 << 12 | 1 << 60));
+
+        board.move_piece(12, 28);
+        assert_eq!(board.piece_at(12), None);
+        assert_eq!(board.pieces(Color::White, PieceType::Pawn), Bitboard(1 << 28));
+
+        assert_eq!(board.remove_piece(60), Some((Color::Black, PieceType::King)));
+        assert_eq!(board.remove_piece(60), None);
+        assert_eq!(board.color_occupancy(Color::Black), Bitboard::EMPTY);
+        assert_eq!(board.occupancy(), Bitboard(1 << 4 | 1 << 28));
+    }
+}
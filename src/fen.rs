@@ -0,0 +1,561 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::board::Board;
+use crate::game::*;
+use crate::position::Position;
+use crate::square::Square;
+use crate::validate::PositionProblem;
+
+// The standard starting position
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// The six space separated fields of a FEN string, in order
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum FenField {
+    Placement,
+    ActiveColor,
+    CastlingRights,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+}
+
+const FIELDS: [FenField; 6] = [
+    FenField::Placement,
+    FenField::ActiveColor,
+    FenField::CastlingRights,
+    FenField::EnPassant,
+    FenField::HalfmoveClock,
+    FenField::FullmoveNumber,
+];
+
+impl fmt::Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FenField::Placement => "piece placement",
+            FenField::ActiveColor => "active color",
+            FenField::CastlingRights => "castling rights",
+            FenField::EnPassant => "en passant square",
+            FenField::HalfmoveClock => "halfmove clock",
+            FenField::FullmoveNumber => "fullmove number",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Why a FEN string could not be read. Positions are byte offsets into the FEN string.
+#[derive(Debug, PartialEq, Clone)]
+pub enum FenError {
+    MissingField { field: FenField },
+    InvalidToken { field: FenField, token: String, position: usize },
+    // A rank (1..=8) whose pieces and empty squares do not add up to 8 files
+    WrongFileCount { rank: usize, files: usize, position: usize },
+    WrongRankCount { ranks: usize },
+    UnexpectedToken { token: String, position: usize },
+    // Only returned by read_fen_strict: the FEN is well formed but the position is not
+    IllegalPosition { problems: Vec<PositionProblem> },
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingField { field } => write!(f, "missing {} field", field),
+            FenError::InvalidToken { field, token, position } =>
+                write!(f, "invalid {} '{}' at position {}", field, token, position),
+            FenError::WrongFileCount { rank, files, position } =>
+                write!(f, "rank {} at position {} has {} files instead of 8", rank, position, files),
+            FenError::WrongRankCount { ranks } => write!(f, "board has {} ranks instead of 8", ranks),
+            FenError::UnexpectedToken { token, position } =>
+                write!(f, "unexpected '{}' at position {} after the fullmove number", token, position),
+            FenError::IllegalPosition { problems } => {
+                let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+                write!(f, "illegal position: {}", problems.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for FenError {}
+
+// A correction read_fen_lenient made to its input
+#[derive(Debug, PartialEq, Clone)]
+pub enum FenFix {
+    // Leading, trailing or repeated whitespace was collapsed into single spaces
+    NormalizedWhitespace,
+    // A missing field, or a counter given as '-', was filled in with a default
+    DefaultedField { field: FenField, value: String },
+    // Tokens after the last field, such as EPD operations, were ignored
+    IgnoredTrailing { tokens: String },
+    DroppedCastlingRight { right: CastlingRights },
+    DroppedEnPassant { square: usize },
+}
+
+impl fmt::Display for FenFix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenFix::NormalizedWhitespace => write!(f, "normalized whitespace"),
+            FenFix::DefaultedField { field, value } => write!(f, "defaulted {} to '{}'", field, value),
+            FenFix::IgnoredTrailing { tokens } => write!(f, "ignored trailing '{}'", tokens),
+            FenFix::DroppedCastlingRight { right } => write!(f, "dropped impossible castling right {:?}", right),
+            FenFix::DroppedEnPassant { square } =>
+                write!(f, "dropped impossible en passant square {}", index_to_position(*square)),
+        }
+    }
+}
+
+// Splits `s` on single spaces, keeping the byte offset of every token
+fn split_with_positions(s: &str, separator: char) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = 0;
+    for (index, ch) in s.char_indices() {
+        if ch == separator {
+            tokens.push((start, &s[start..index]));
+            start = index + ch.len_utf8();
+        }
+    }
+    tokens.push((start, &s[start..]));
+    tokens
+}
+
+fn invalid(field: FenField, token: &str, position: usize) -> FenError {
+    FenError::InvalidToken { field, token: token.to_string(), position }
+}
+
+impl Position {
+    pub fn read_fen(fen: &str) -> Result<Position, FenError> {
+        let mut parsed = Position::empty();
+
+        let fields = split_with_positions(fen, ' ');
+        if fields.len() > FIELDS.len() {
+            let (position, token) = fields[FIELDS.len()];
+            return Err(FenError::UnexpectedToken { token: token.to_string(), position });
+        }
+        if fields.len() < FIELDS.len() {
+            return Err(FenError::MissingField { field: FIELDS[fields.len()] });
+        }
+
+        let (board_position, boardstr) = fields[0];
+        let mut rows = split_with_positions(boardstr, '/');
+        if rows.len() != 8 {
+            return Err(FenError::WrongRankCount { ranks: rows.len() });
+        }
+        rows.reverse();
+
+        for (row_index, (row_position, row)) in rows.into_iter().enumerate() {
+            match parse_row(row, row_index * 8, &mut parsed.board) {
+                Ok(()) => (),
+                Err(RowError::InvalidToken(token, offset)) =>
+                    return Err(invalid(FenField::Placement, &token, board_position + row_position + offset)),
+                Err(RowError::WrongFileCount(files)) =>
+                    return Err(FenError::WrongFileCount { rank: row_index + 1, files, position: board_position + row_position }),
+            }
+        }
+
+        let (position, color_to_move) = fields[1];
+        parsed.active_color = match color_to_move{
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(invalid(FenField::ActiveColor, color_to_move, position)),
+        };
+
+        let (position, castling_rights) = fields[2];
+        let mut castling = CastlingRights::NONE;
+        if castling_rights != "-" {
+            if castling_rights.is_empty() {
+                return Err(invalid(FenField::CastlingRights, castling_rights, position));
+            }
+            for (offset, ch) in castling_rights.char_indices(){
+                let right = match ch {
+                    'K' => CastlingRights::WHITEKINGSIDE,
+                    'Q' => CastlingRights::WHITEQUEENSIDE,
+                    'k' => CastlingRights::BLACKKINGSIDE,
+                    'q' => CastlingRights::BLACKQUEENSIDE,
+                    other => return Err(invalid(FenField::CastlingRights, &other.to_string(), position + offset)),
+                };
+                if castling.contains(right) {
+                    return Err(invalid(FenField::CastlingRights, &ch.to_string(), position + offset));
+                }
+                castling |= right;
+            }
+        }
+        parsed.castling_rights = castling;
+
+        let (position, en_passant) = fields[3];
+        parsed.en_passant = match en_passant {
+            "-" => None,
+            s => match position_to_bit(s).ok().and_then(Square::from_bitboard) {
+                Some(square) => Some(square),
+                None => return Err(invalid(FenField::EnPassant, s, position)),
+            }
+        };
+
+        let (position, ply) = fields[4];
+        parsed.ply = ply.parse()
+            .map_err(|_| invalid(FenField::HalfmoveClock, ply, position))?;
+
+        let (position, fullmoves) = fields[5];
+        // Numbering starts at 1, so 0 is as invalid as a negative number
+        parsed.fullmoves = fullmoves.parse().ok()
+            .filter(|&fullmoves| fullmoves > 0)
+            .ok_or_else(|| invalid(FenField::FullmoveNumber, fullmoves, position))?;
+
+        parsed.refresh_zobrist_key();
+        Ok(parsed)
+    }
+
+    // Like read_fen, but also rejects positions that fail Position::validate
+    pub fn read_fen_strict(fen: &str) -> Result<Position, FenError> {
+        let parsed = Position::read_fen(fen)?;
+        let problems = parsed.validate();
+        if !problems.is_empty() {
+            return Err(FenError::IllegalPosition { problems });
+        }
+        Ok(parsed)
+    }
+
+    // Reads FEN and EPD strings as found in the wild. Whitespace is normalized, missing
+    // castling and en passant fields default to '-', missing or '-' counters to 0 and 1,
+    // a fullmove number of 0 to 1, and anything after the counters is ignored. Castling
+    // rights without their king and rook and en passant squares no double push could have
+    // produced are dropped.
+    // Returns the position with the list of fixes applied; positions in errors refer to the
+    // normalized string.
+    pub fn read_fen_lenient(fen: &str) -> Result<(Position, Vec<FenFix>), FenError> {
+        let mut fixes = vec![];
+        let tokens: Vec<&str> = fen.split_whitespace().collect();
+        if tokens.join(" ") != fen {
+            fixes.push(FenFix::NormalizedWhitespace);
+        }
+        if tokens.len() < 2 {
+            return Err(FenError::MissingField { field: FIELDS[tokens.len()] });
+        }
+
+        let mut fields: Vec<String> = tokens[..2].iter().map(|token| token.to_string()).collect();
+        let mut rest = &tokens[2..];
+        let is_counter = |token: &str| token.parse::<usize>().is_ok();
+        // A number is never castling rights or a square, so it means those fields were left out
+        for (field, default) in [(FenField::CastlingRights, "-"), (FenField::EnPassant, "-")] {
+            match rest.split_first() {
+                Some((token, remaining)) if !is_counter(token) => {
+                    fields.push(token.to_string());
+                    rest = remaining;
+                }
+                _ => {
+                    fields.push(default.to_string());
+                    fixes.push(FenFix::DefaultedField { field, value: default.to_string() });
+                }
+            }
+        }
+        // Counters are optional in EPD, so the first token that is not a number ends the FEN
+        for (field, default) in [(FenField::HalfmoveClock, "0"), (FenField::FullmoveNumber, "1")] {
+            match rest.split_first() {
+                Some((token, remaining)) if *token == "-" || (field == FenField::FullmoveNumber && *token == "0") => {
+                    fields.push(default.to_string());
+                    fixes.push(FenFix::DefaultedField { field, value: default.to_string() });
+                    rest = remaining;
+                }
+                Some((token, remaining)) if is_counter(token) => {
+                    fields.push(token.to_string());
+                    rest = remaining;
+                }
+                _ => {
+                    fields.push(default.to_string());
+                    fixes.push(FenFix::DefaultedField { field, value: default.to_string() });
+                }
+            }
+        }
+        if !rest.is_empty() {
+            fixes.push(FenFix::IgnoredTrailing { tokens: rest.join(" ") });
+        }
+
+        let mut parsed = Position::read_fen(&fields.join(" "))?;
+
+        for problem in parsed.validate() {
+            if let PositionProblem::CastlingRightWithoutPieces { right } = problem {
+                parsed.castling_rights.remove(right);
+                fixes.push(FenFix::DroppedCastlingRight { right });
+            }
+        }
+        if let Some(square) = parsed.en_passant {
+            let square = square.index();
+            if !parsed.is_possible_en_passant(square) {
+                parsed.en_passant = None;
+                fixes.push(FenFix::DroppedEnPassant { square });
+            }
+        }
+        parsed.refresh_zobrist_key();
+
+        Ok((parsed, fixes))
+    }
+
+    // Writes the position back out in FEN, the inverse of read_fen
+    pub fn to_fen(&self) -> String {
+        let mut rows = vec![];
+        for rank in (0..8).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_at(rank * 8 + file) {
+                    None => empty += 1,
+                    Some((color, piece_type)) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece_char(color, piece_type));
+                    }
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            rows.push(row);
+        }
+
+        let color = match self.active_color {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for (right, ch) in [
+            (CastlingRights::WHITEKINGSIDE, 'K'),
+            (CastlingRights::WHITEQUEENSIDE, 'Q'),
+            (CastlingRights::BLACKKINGSIDE, 'k'),
+            (CastlingRights::BLACKQUEENSIDE, 'q'),
+        ] {
+            if self.castling_rights.contains(right) {
+                castling.push(ch);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!("{} {} {} {} {} {}", rows.join("/"), color, castling, en_passant, self.ply, self.fullmoves)
+    }
+}
+
+// The FEN readers for a Game start it from the parsed position with an empty history
+impl Game {
+    pub fn read_fen(fen: &str) -> Result<Game, FenError> {
+        Position::read_fen(fen).map(Game::new)
+    }
+
+    pub fn read_fen_strict(fen: &str) -> Result<Game, FenError> {
+        Position::read_fen_strict(fen).map(Game::new)
+    }
+
+    pub fn read_fen_lenient(fen: &str) -> Result<(Game, Vec<FenFix>), FenError> {
+        Position::read_fen_lenient(fen).map(|(parsed, fixes)| (Game::new(parsed), fixes))
+    }
+
+    pub fn to_fen(&self) -> String {
+        self.position.to_fen()
+    }
+}
+
+enum RowError {
+    // The offending token and its byte offset within the row
+    InvalidToken(String, usize),
+    WrongFileCount(usize),
+}
+
+// Parses one rank of the placement field onto `board`, starting at square `piece_position`
+fn parse_row(row: &str, mut piece_position: usize, board: &mut Board) -> Result<(), RowError> {
+    let mut files = 0;
+    let mut color;
+
+    macro_rules! add_piece {
+        ($piece_type:ident) => {
+            {
+                files += 1;
+                // Pieces past the eighth file would land on the next rank, so they are only counted
+                if files <= 8 {
+                    board.put_piece(piece_position, color, PieceType::$piece_type);
+                    piece_position += 1;
+                }
+            }
+
+        };
+    }
+
+    for (offset, ch) in row.char_indices() {
+        let is_upper =  ch.is_ascii_uppercase();
+        color = if is_upper {Color::White} else {Color::Black};
+        match ch.to_ascii_lowercase(){
+            'r' => add_piece!(Rook),
+            'n' => add_piece!(Knight),
+            'b' => add_piece!(Bishop),
+            'q' => add_piece!(Queen),
+            'p' => add_piece!(Pawn),
+            'k' => add_piece!(King),
+            num => {
+                match num.to_digit(10){
+                    Some(number) if (1..=8).contains(&number) => {
+                        files += number as usize;
+                        piece_position += number as usize;
+                    }
+                    _ => return Err(RowError::InvalidToken(ch.to_string(), offset)),
+                }
+            }
+
+        }
+    }
+
+    if files != 8 {
+        return Err(RowError::WrongFileCount(files));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FENS: [&str; 8] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        "4k3/8/8/8/8/8/8/4K2R w K - 12 57",
+    ];
+
+    #[test]
+    fn to_fen_writes_read_fen_input_back() {
+        for fen in FENS {
+            assert_eq!(Position::read_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn read_fen_round_trips_to_fen() {
+        // Walk a few plies from each position so the corpus also covers positions
+        // produced by make_move, with en passant squares and lost castling rights
+        for fen in FENS {
+            let mut game = Game::read_fen(fen).unwrap();
+            for step in 0..12 {
+                let parsed = Position::read_fen(&game.to_fen()).unwrap();
+                assert_eq!(parsed, game.position);
+
+                let moves = game.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                game.make_move(moves[(step * 7 + 3) % moves.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn reports_missing_fields() {
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8/8").err(), Some(FenError::MissingField { field: FenField::ActiveColor }));
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8/8 w - -").err(), Some(FenError::MissingField { field: FenField::HalfmoveClock }));
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8/8 w - - 0 1 extra").err(),
+            Some(FenError::UnexpectedToken { token: "extra".to_string(), position: 26 }));
+    }
+
+    #[test]
+    fn reports_invalid_tokens_with_position() {
+        let cases = [
+            ("8/8/8/8/8/8/8/8 x - - 0 1", FenField::ActiveColor, "x", 16),
+            ("8/8/8/8/8/8/8/8 w KX - 0 1", FenField::CastlingRights, "X", 19),
+            ("8/8/8/8/8/8/8/8 w KK - 0 1", FenField::CastlingRights, "K", 19),
+            ("8/8/8/8/8/8/8/8 w - e9 0 1", FenField::EnPassant, "e9", 20),
+            ("8/8/8/8/8/8/8/8 w - - x 1", FenField::HalfmoveClock, "x", 22),
+            ("8/8/8/8/8/8/8/8 w - - 0 -1", FenField::FullmoveNumber, "-1", 24),
+            ("8/8/8/8/8/8/8/8 w - - 0 0", FenField::FullmoveNumber, "0", 24),
+            ("8/8/8/8/8/8/8/7x w - - 0 1", FenField::Placement, "x", 15),
+            ("8/8/8/8/8/8/8/0 w - - 0 1", FenField::Placement, "0", 14),
+        ];
+        for (fen, field, token, position) in cases {
+            assert_eq!(Position::read_fen(fen).err(),
+                Some(FenError::InvalidToken { field, token: token.to_string(), position }), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_board_shape() {
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8 w - - 0 1").err(), Some(FenError::WrongRankCount { ranks: 7 }));
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8/8/8 w - - 0 1").err(), Some(FenError::WrongRankCount { ranks: 9 }));
+        assert_eq!(Position::read_fen("8/8/8/8/8/8/8/7 w - - 0 1").err(),
+            Some(FenError::WrongFileCount { rank: 1, files: 7, position: 14 }));
+        assert_eq!(Position::read_fen("rnbqkbnrp/8/8/8/8/8/8/8 w - - 0 1").err(),
+            Some(FenError::WrongFileCount { rank: 8, files: 9, position: 0 }));
+        assert_eq!(Position::read_fen("88/8/8/8/8/8/8/8 w - - 0 1").err(),
+            Some(FenError::WrongFileCount { rank: 8, files: 16, position: 0 }));
+        assert!(Position::read_fen("44/8/8/8/8/8/8/8 w - - 0 1").is_ok());
+    }
+
+    #[test]
+    fn strict_mode_rejects_illegal_positions() {
+        assert!(Position::read_fen_strict(FENS[0]).is_ok());
+        assert!(Position::read_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_ok());
+        assert_eq!(Position::read_fen_strict("8/8/8/8/8/8/8/8 w - - 0 1").err(), Some(FenError::IllegalPosition { problems: vec![
+            PositionProblem::WrongKingCount { color: Color::White, count: 0 },
+            PositionProblem::WrongKingCount { color: Color::Black, count: 0 },
+        ]}));
+    }
+
+    #[test]
+    fn lenient_mode_accepts_sloppy_input() {
+        let (position, fixes) = Position::read_fen_lenient("  rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR   w KQkq -  ").unwrap();
+        assert_eq!(position.to_fen(), FENS[0]);
+        assert_eq!(fixes, vec![
+            FenFix::NormalizedWhitespace,
+            FenFix::DefaultedField { field: FenField::HalfmoveClock, value: "0".to_string() },
+            FenFix::DefaultedField { field: FenField::FullmoveNumber, value: "1".to_string() },
+        ]);
+
+        let (position, fixes) = Position::read_fen_lenient("4k3/8/8/8/8/8/8/4K3 b - - - 40 bm Kd7; id \"test\";").unwrap();
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 40");
+        assert_eq!(fixes, vec![
+            FenFix::DefaultedField { field: FenField::HalfmoveClock, value: "0".to_string() },
+            FenFix::IgnoredTrailing { tokens: "bm Kd7; id \"test\";".to_string() },
+        ]);
+
+        let (position, fixes) = Position::read_fen_lenient("4k3/8/8/8/8/8/8/4K3 w 0 1").unwrap();
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(fixes, vec![
+            FenFix::DefaultedField { field: FenField::CastlingRights, value: "-".to_string() },
+            FenFix::DefaultedField { field: FenField::EnPassant, value: "-".to_string() },
+        ]);
+
+        let (position, fixes) = Position::read_fen_lenient("4k3/8/8/8/8/8/8/4K3 b - - 3 0").unwrap();
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 3 1");
+        assert_eq!(fixes, vec![FenFix::DefaultedField { field: FenField::FullmoveNumber, value: "1".to_string() }]);
+
+        let (position, _) = Position::read_fen_lenient("4k3/8/8/8/8/8/8/4K3 w").unwrap();
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(Position::read_fen_lenient("4k3/8/8/8/8/8/8/4K3").err(),
+            Some(FenError::MissingField { field: FenField::ActiveColor }));
+    }
+
+    #[test]
+    fn lenient_mode_drops_impossible_state() {
+        let (position, fixes) = Position::read_fen_lenient("r3k3/8/8/8/8/8/8/4K2R w KQkq e6 0 1").unwrap();
+        assert_eq!(position.to_fen(), "r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+        assert_eq!(fixes, vec![
+            FenFix::DroppedCastlingRight { right: CastlingRights::WHITEQUEENSIDE },
+            FenFix::DroppedCastlingRight { right: CastlingRights::BLACKKINGSIDE },
+            FenFix::DroppedEnPassant { square: 44 },
+        ]);
+
+        // Possible en passant squares stay, even when no pawn can capture on them
+        for fen in ["rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"] {
+            let (position, fixes) = Position::read_fen_lenient(fen).unwrap();
+            assert_eq!(position.to_fen(), fen);
+            assert!(fixes.is_empty());
+        }
+    }
+}
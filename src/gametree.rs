@@ -0,0 +1,388 @@
+use std::time::Duration;
+
+use crate::fen::START_FEN;
+use crate::game::*;
+use crate::moves::Move;
+use crate::pgn::{Eval, PgnError, PgnGame, PgnMove, PgnReader};
+use crate::position::Position;
+
+// A move in a game tree and what follows it. The first child is the main continuation,
+// the others are variations on it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveNode {
+    pub mv: Move,
+    pub nags: Vec<u8>,
+    pub comments: Vec<String>,
+    // Comments before the move when it starts a variation
+    pub starting_comments: Vec<String>,
+    pub clock: Option<Duration>,
+    pub eval: Option<Eval>,
+    pub children: Vec<MoveNode>,
+}
+
+impl MoveNode {
+    pub fn new(mv: Move) -> MoveNode {
+        MoveNode { mv, nags: vec![], comments: vec![], starting_comments: vec![], clock: None, eval: None, children: vec![] }
+    }
+}
+
+// An annotated game with all its variations and a cursor pointing at one position in it.
+// The cursor starts before the first move.
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    pub tags: Vec<(String, String)>,
+    // Comments before the first move of the game
+    pub comments: Vec<String>,
+    pub result: Option<String>,
+    // The possible first moves, the mainline's first
+    roots: Vec<MoveNode>,
+    // The child index taken at each level to reach the cursor, and the position
+    // before the first move followed by the position after each move on the path
+    path: Vec<usize>,
+    positions: Vec<Position>,
+}
+
+impl GameTree {
+    pub fn new(start: Position) -> GameTree {
+        GameTree { tags: vec![], comments: vec![], result: None, roots: vec![], path: vec![], positions: vec![start] }
+    }
+
+    pub fn start(&self) -> Position {
+        self.positions[0]
+    }
+
+    // The position at the cursor
+    pub fn position(&self) -> Position {
+        *self.positions.last().unwrap()
+    }
+
+    // The move that led to the cursor, None at the start
+    pub fn current(&self) -> Option<&MoveNode> {
+        let (&last, parents) = self.path.split_last()?;
+        Some(&self.children_at(parents)[last])
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut MoveNode> {
+        let (&last, parents) = self.path.split_last()?;
+        let parents = parents.to_vec();
+        Some(&mut self.children_at_mut(&parents)[last])
+    }
+
+    // The moves that can follow the cursor, the main continuation first
+    pub fn continuations(&self) -> &[MoveNode] {
+        self.children_at(&self.path)
+    }
+
+    // The moves from the start to the cursor
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        let mut children = &self.roots;
+        for &index in &self.path {
+            moves.push(children[index].mv);
+            children = &children[index].children;
+        }
+        moves
+    }
+
+    // A Game at the cursor, with the moves that led there as its history
+    pub fn game(&self) -> Game {
+        let mut game = Game::new(self.start());
+        for mv in self.moves() {
+            game.make_move(mv);
+        }
+        game
+    }
+
+    // Plays a move from the cursor and moves onto it. A move already in the tree is
+    // followed, a new one is added as the last variation. False if the move is illegal.
+    pub fn play(&mut self, mv: Move) -> bool {
+        if !self.position().legal_moves().contains(&mv) {
+            return false;
+        }
+        let path = self.path.clone();
+        let children = self.children_at_mut(&path);
+        let index = match children.iter().position(|child| child.mv == mv) {
+            Some(index) => index,
+            None => {
+                children.push(MoveNode::new(mv));
+                children.len() - 1
+            }
+        };
+        self.enter(index);
+        true
+    }
+
+    // Follows the main continuation
+    pub fn forward(&mut self) -> Option<Move> {
+        self.forward_to(0)
+    }
+
+    // Follows the continuation with the given index, 0 being the main one
+    pub fn forward_to(&mut self, index: usize) -> Option<Move> {
+        if index >= self.continuations().len() {
+            return None;
+        }
+        Some(self.enter(index))
+    }
+
+    // Takes back the move at the cursor
+    pub fn back(&mut self) -> Option<Move> {
+        let mv = self.current()?.mv;
+        self.path.pop();
+        self.positions.pop();
+        Some(mv)
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.path.clear();
+        self.positions.truncate(1);
+    }
+
+    // Moves the variation holding the cursor one place up among its siblings, making it
+    // the main continuation when it was the first variation. False on the mainline.
+    pub fn promote_variation(&mut self) -> bool {
+        let Some(level) = self.path.iter().rposition(|&index| index != 0) else {
+            return false;
+        };
+        let index = self.path[level];
+        let parents = self.path[..level].to_vec();
+        self.children_at_mut(&parents).swap(index, index - 1);
+        self.path[level] = index - 1;
+        true
+    }
+
+    // Removes the variation holding the cursor and moves the cursor to where it branched
+    // off. False on the mainline.
+    pub fn delete_variation(&mut self) -> bool {
+        let Some(level) = self.path.iter().rposition(|&index| index != 0) else {
+            return false;
+        };
+        let index = self.path[level];
+        self.path.truncate(level);
+        self.positions.truncate(level + 1);
+        let parents = self.path.clone();
+        self.children_at_mut(&parents).remove(index);
+        true
+    }
+
+    fn enter(&mut self, index: usize) -> Move {
+        let mv = self.continuations()[index].mv;
+        let mut position = self.position();
+        position.make_move(mv);
+        self.path.push(index);
+        self.positions.push(position);
+        mv
+    }
+
+    fn children_at(&self, path: &[usize]) -> &[MoveNode] {
+        path.iter().fold(&self.roots, |children, &index| &children[index].children)
+    }
+
+    fn children_at_mut(&mut self, path: &[usize]) -> &mut Vec<MoveNode> {
+        path.iter().fold(&mut self.roots, |children, &index| &mut children[index].children)
+    }
+}
+
+// Conversion from and to the PGN game record. Reading starts with the cursor at the start.
+impl GameTree {
+    pub fn from_pgn_game(pgn: &PgnGame) -> GameTree {
+        GameTree {
+            tags: pgn.tags.clone(),
+            comments: pgn.comments.clone(),
+            result: pgn.result.clone(),
+            roots: nodes_from_line(&pgn.moves),
+            ..GameTree::new(pgn.start)
+        }
+    }
+
+    pub fn to_pgn_game(&self) -> PgnGame {
+        let moves = line_from_nodes(&self.roots);
+        let mut game = Game::new(self.start());
+        for played in &moves {
+            game.make_move(played.mv);
+        }
+        PgnGame {
+            tags: self.tags.clone(),
+            start: self.start(),
+            game,
+            moves,
+            comments: self.comments.clone(),
+            result: self.result.clone(),
+        }
+    }
+
+    // The first game in `pgn`, or an empty tree from the start position when there is none
+    pub fn read_pgn(pgn: &str) -> Result<GameTree, PgnError> {
+        match PgnReader::new(pgn.as_bytes()).next() {
+            Some(game) => game.map(|game| GameTree::from_pgn_game(&game)),
+            None => Ok(GameTree::new(Position::read_fen(START_FEN).unwrap())),
+        }
+    }
+
+    pub fn to_pgn(&self) -> String {
+        self.to_pgn_game().to_pgn()
+    }
+}
+
+// The first move of a line with the rest of the line under it, followed by its variations
+fn nodes_from_line(moves: &[PgnMove]) -> Vec<MoveNode> {
+    let Some((first, rest)) = moves.split_first() else {
+        return vec![];
+    };
+    let mut node = MoveNode {
+        nags: first.nags.clone(),
+        comments: first.comments.clone(),
+        starting_comments: first.starting_comments.clone(),
+        clock: first.clock,
+        eval: first.eval,
+        ..MoveNode::new(first.mv)
+    };
+    node.children = nodes_from_line(rest);
+
+    let mut siblings = vec![node];
+    for variation in &first.variations {
+        siblings.extend(nodes_from_line(variation));
+    }
+    siblings
+}
+
+// The line following the first node of `siblings`, with the other siblings as its variations
+fn line_from_nodes(mut siblings: &[MoveNode]) -> Vec<PgnMove> {
+    let mut line = vec![];
+    while let Some((main, alternatives)) = siblings.split_first() {
+        line.push(PgnMove {
+            nags: main.nags.clone(),
+            comments: main.comments.clone(),
+            starting_comments: main.starting_comments.clone(),
+            clock: main.clock,
+            eval: main.eval,
+            variations: alternatives.iter().map(|alternative| line_from_nodes(std::slice::from_ref(alternative))).collect(),
+            ..PgnMove::new(main.mv)
+        });
+        siblings = &main.children;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PGN: &str = "1. e4 {King's pawn} e5 (1... c5 2. Nf3 (2. c3) 2... d6) (1... e6) 2. Nf3 Nc6 $1 *";
+
+    fn san_moves(tree: &GameTree) -> Vec<String> {
+        let mut position = tree.start();
+        tree.moves().into_iter().map(|mv| {
+            let san = position.to_san(mv);
+            position.make_move(mv);
+            san
+        }).collect()
+    }
+
+    fn play(tree: &mut GameTree, san: &str) {
+        let mv = tree.position().parse_san(san).unwrap();
+        assert!(tree.play(mv));
+    }
+
+    #[test]
+    fn navigates_mainline_and_variations() {
+        let mut tree = GameTree::read_pgn(PGN).unwrap();
+        assert_eq!(tree.current(), None);
+        assert_eq!(tree.back(), None);
+
+        assert_eq!(tree.forward().map(|mv| mv.to_string()), Some("e2e4".to_string()));
+        assert_eq!(tree.current().unwrap().comments, ["King's pawn"]);
+        assert_eq!(tree.continuations().len(), 3);
+        assert_eq!(tree.forward_to(3), None);
+        assert_eq!(tree.forward_to(1).map(|mv| mv.to_string()), Some("c7c5".to_string()));
+        tree.forward_to(1);
+        assert_eq!(san_moves(&tree), ["e4", "c5", "c3"]);
+        assert_eq!(tree.position().to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/2P5/PP1P1PPP/RNBQKBNR b KQkq - 0 2");
+        assert_eq!(tree.game().position, tree.position());
+        assert_eq!(tree.game().moves().collect::<Vec<_>>(), tree.moves());
+
+        assert_eq!(tree.back().map(|mv| mv.to_string()), Some("c2c3".to_string()));
+        tree.back();
+        tree.forward();
+        tree.forward();
+        tree.forward();
+        assert_eq!(san_moves(&tree), ["e4", "e5", "Nf3", "Nc6"]);
+        assert_eq!(tree.current().unwrap().nags, [1]);
+        assert_eq!(tree.forward(), None);
+
+        tree.go_to_start();
+        assert_eq!(tree.position(), tree.start());
+    }
+
+    #[test]
+    fn plays_new_moves_as_variations() {
+        let mut tree = GameTree::read_pgn(PGN).unwrap();
+        play(&mut tree, "e4");
+        play(&mut tree, "c5");
+        assert_eq!(tree.path, [0, 1]);
+        play(&mut tree, "Nc3");
+        assert_eq!(tree.path, [0, 1, 2]);
+        assert!(!tree.play(Move::new(0, 63, crate::moves::MoveFlags::NONE)));
+        tree.current_mut().unwrap().comments.push("Closed".to_string());
+        assert!(tree.to_pgn().contains("(2. c3) (2. Nc3 {Closed})"));
+
+        let mut empty = GameTree::read_pgn("").unwrap();
+        play(&mut empty, "d4");
+        play(&mut empty, "d5");
+        empty.result = Some("*".to_string());
+        assert!(empty.to_pgn().ends_with("\n1. d4 d5 *\n\n"));
+    }
+
+    #[test]
+    fn promotes_variations() {
+        let mut tree = GameTree::read_pgn(PGN).unwrap();
+        assert!(!tree.promote_variation());
+        tree.forward();
+        tree.forward_to(2);
+        assert!(tree.promote_variation());
+        assert_eq!(tree.path, [0, 1]);
+        assert!(tree.to_pgn().contains("1. e4 {King's pawn} 1... e5 (1... e6) (1... c5"));
+
+        assert!(tree.promote_variation());
+        assert_eq!(tree.path, [0, 0]);
+        assert_eq!(san_moves(&tree), ["e4", "e6"]);
+        assert!(tree.to_pgn().contains("1. e4 {King's pawn} 1... e6 (1... e5 2. Nf3 Nc6 $1) (1... c5"));
+        assert!(!tree.promote_variation());
+    }
+
+    #[test]
+    fn deletes_variations() {
+        let mut tree = GameTree::read_pgn(PGN).unwrap();
+        tree.forward();
+        tree.forward_to(1);
+        tree.forward_to(1);
+        assert!(tree.delete_variation());
+        assert_eq!(san_moves(&tree), ["e4", "c5"]);
+        assert!(tree.to_pgn().contains("(1... c5 2. Nf3 d6) (1... e6)"));
+
+        assert!(tree.delete_variation());
+        assert_eq!(san_moves(&tree), ["e4"]);
+        assert_eq!(tree.position(), tree.game().position);
+        assert!(tree.to_pgn().contains("e5 (1... e6) 2. Nf3"));
+        tree.forward();
+        assert!(!tree.delete_variation());
+    }
+
+    #[test]
+    fn converts_to_and_from_pgn() {
+        let pgn = "[Event \"Test\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n\
+            {Endgame} 40... Kd7 {[%eval 0.50] [%clk 0:00:10]} (40... Kf7 $2 41. e4 ({Also} 41. e3)) 41. e4 1/2-1/2";
+        let pgn_game = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+        let tree = GameTree::from_pgn_game(&pgn_game);
+        assert_eq!(tree.tags, pgn_game.tags);
+        assert_eq!(tree.comments, ["Endgame"]);
+        assert_eq!(tree.continuations()[0].clock, Some(Duration::from_secs(10)));
+        assert_eq!(tree.continuations()[1].children[1].starting_comments, ["Also"]);
+
+        let again = tree.to_pgn_game();
+        assert_eq!(again.moves, pgn_game.moves);
+        assert_eq!(again.game.position, pgn_game.game.position);
+        assert_eq!(again.to_pgn(), pgn_game.to_pgn());
+        assert_eq!(GameTree::read_pgn(&tree.to_pgn()).unwrap().to_pgn(), tree.to_pgn());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::attacks::KING_ATTACKS;
+use crate::bitboard::Bitboard;
+
+// The king table of crate::attacks
+pub struct KingAttacks(&'static [Bitboard; 64]);
+
+impl KingAttacks {
+    pub const fn init() -> Self {
+        Self(&KING_ATTACKS)
+    }
+
+    // Squares a king standing on `square` attacks
+    pub fn attacks(&self, square: usize) -> Bitboard {
+        self.0[square]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::Square;
+
+    #[test]
+    fn corners_edges_and_centre() {
+        let king_attacks = KingAttacks::init();
+        for square in Square::ALL {
+            let on_file_edge = matches!(square.file().index(), 0 | 7);
+            let on_rank_edge = matches!(square.rank().index(), 0 | 7);
+            let expected = match (on_file_edge, on_rank_edge) {
+                (true, true) => 3,
+                (true, false) | (false, true) => 5,
+                (false, false) => 8,
+            };
+            assert_eq!(king_attacks.attacks(square.index()).popcount(), expected, "king on {}", square);
+        }
+        assert_eq!(king_attacks.attacks(Square::A1.index()), Square::B1.bitboard() | Square::A2.bitboard() | Square::B2.bitboard());
+        assert_eq!(king_attacks.attacks(Square::H8.index()), Square::G8.bitboard() | Square::G7.bitboard() | Square::H7.bitboard());
+    }
+
+    #[test]
+    fn attacks_do_not_wrap_around_files() {
+        let king_attacks = KingAttacks::init();
+        for square in Square::ALL {
+            for target in king_attacks.attacks(square.index()) {
+                assert!(square.file().index().abs_diff(target.file().index()) <= 1, "king on {} attacks {}", square, target);
+                assert!(square.rank().index().abs_diff(target.rank().index()) <= 1, "king on {} attacks {}", square, target);
+                assert_ne!(square, target);
+            }
+        }
+    }
+}
@@ -1,40 +1,29 @@
+use crate::attacks::KNIGHT_ATTACKS;
+use crate::bitboard::Bitboard;
 
-use crate::utils::*;
-
-type Bitboard = u64;
-
-pub struct KnightAttacks(Vec<Bitboard>);
+// The knight table of crate::attacks
+pub struct KnightAttacks(&'static [Bitboard; 64]);
 
 impl KnightAttacks {
-    fn init() -> Self {
-        let mut attacks = vec![];
-        for row in 1..=8 {
-            for col in 1..=8{
-                let attacks_from_this_square = knight_attacks(row, col);
-                attacks.push(attacks_from_this_square);
-            }
-        }
-        Self(attacks)
+    pub const fn init() -> Self {
+        Self(&KNIGHT_ATTACKS)
     }
-}
 
-fn knight_attacks(row: i32, col: i32) -> Bitboard {
-    let attack_pairs = [(1,2), (1,-2), (-1,2), (-1,-2), (2,1), (2,-1), (-2, 1), (-2, -1)];
-    let mut bitboard:Bitboard = 0;
-    for (r,c) in attack_pairs {
-        bitboard |= set_bit(row + r, col + c);
+    // Squares a knight standing on `square` attacks
+    pub fn attacks(&self, square: usize) -> Bitboard {
+        self.0[square]
     }
-    return bitboard;
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::*;
 
     #[test]
     fn test_knight_attacks_can_initialize() {
-       let knight_attacks = KnightAttacks::init(); 
+       let _knight_attacks = KnightAttacks::init(); 
     }
 
     #[test]
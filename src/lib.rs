@@ -0,0 +1,26 @@
+pub mod utils;
+pub mod game;
+pub mod bitboard;
+pub mod square;
+pub mod board;
+pub mod position;
+pub mod fen;
+pub mod attacks;
+pub mod rayattacks;
+pub mod knightattacks;
+pub mod kingattacks;
+pub mod pawnattacks;
+pub mod magic;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub mod pext;
+pub mod sliders;
+pub mod moves;
+pub mod movegen;
+pub mod makemove;
+pub mod perft;
+pub mod validate;
+pub mod san;
+pub mod pgn;
+pub mod pgnwriter;
+pub mod gametree;
+pub mod zobrist;
@@ -0,0 +1,195 @@
+use crate::bitboard::Bitboard;
+use crate::square::Square;
+
+pub(crate) const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+pub(crate) const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+// Lookup data for one slider on one square. The occupancy bits inside `mask`
+// are hashed by multiplying with `magic` and keeping the top bits, which gives
+// the index of the attack set in the shared table after adding `offset`.
+#[derive(Debug, Default, Copy, Clone)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: Bitboard) -> usize {
+        ((occupancy & self.mask).0.wrapping_mul(self.magic) >> self.shift) as usize + self.offset
+    }
+}
+
+// Slider attacks looked up with magic bitboards. The magic numbers are searched
+// for when the tables are built, so there is no hard-coded list to keep in sync.
+pub struct MagicAttacks {
+    bishops: [Magic; 64],
+    rooks: [Magic; 64],
+    table: Vec<Bitboard>,
+}
+
+impl MagicAttacks {
+    pub fn init() -> Self {
+        let mut random = Xorshift(0x2545_F491_4F6C_DD1D);
+        let mut table = vec![];
+        let mut bishops = [Magic::default(); 64];
+        let mut rooks = [Magic::default(); 64];
+        for square in Square::ALL {
+            bishops[square.index()] = find_magic(square, &BISHOP_DIRECTIONS, &mut random, &mut table);
+            rooks[square.index()] = find_magic(square, &ROOK_DIRECTIONS, &mut random, &mut table);
+        }
+        Self { bishops, rooks, table }
+    }
+
+    pub fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.table[self.bishops[square].index(occupancy)]
+    }
+
+    pub fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.table[self.rooks[square].index(occupancy)]
+    }
+
+    pub fn queen_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.bishop_attacks(square, occupancy) | self.rook_attacks(square, occupancy)
+    }
+}
+
+// Squares whose occupancy can change a slider's attacks from `square`. The last
+// square of each ray is left out since it is attacked whether it is occupied or not.
+pub fn relevant_occupancy(square: Square, directions: &[(i32, i32)]) -> Bitboard {
+    let mut mask = Bitboard::EMPTY;
+    for &(files, ranks) in directions {
+        let mut current = square;
+        while let Some(next) = current.offset(files, ranks) {
+            if next.offset(files, ranks).is_none() {
+                break;
+            }
+            mask |= next.bitboard();
+            current = next;
+        }
+    }
+    mask
+}
+
+// Attacks found by walking each direction square by square, up to and including the first blocker
+pub fn sliding_attacks(square: Square, occupancy: Bitboard, directions: &[(i32, i32)]) -> Bitboard {
+    let mut attacks = Bitboard::EMPTY;
+    for &(files, ranks) in directions {
+        let mut current = square;
+        while let Some(next) = current.offset(files, ranks) {
+            attacks |= next.bitboard();
+            if occupancy.contains(next) {
+                break;
+            }
+            current = next;
+        }
+    }
+    attacks
+}
+
+// Every subset of `mask` in increasing order, enumerated with the carry-rippler trick.
+// The n-th subset is the one whose mask bits, packed together, spell out n.
+pub(crate) fn occupancy_subsets(mask: Bitboard) -> Vec<Bitboard> {
+    let mut subsets = vec![];
+    let mut subset = 0_u64;
+    loop {
+        subsets.push(Bitboard(subset));
+        subset = subset.wrapping_sub(mask.0) & mask.0;
+        if subset == 0 {
+            return subsets;
+        }
+    }
+}
+
+// Tries random sparse numbers until one maps every occupancy subset of the mask to a slot
+// without two different attack sets colliding, then appends the filled slots to `table`
+fn find_magic(square: Square, directions: &[(i32, i32)], random: &mut Xorshift, table: &mut Vec<Bitboard>) -> Magic {
+    let mask = relevant_occupancy(square, directions);
+    let bits = mask.popcount();
+    let shift = 64 - bits;
+
+    let occupancies = occupancy_subsets(mask);
+    let attacks: Vec<Bitboard> = occupancies.iter()
+        .map(|&occupancy| sliding_attacks(square, occupancy, directions))
+        .collect();
+
+    // Slots are stamped with the attempt that filled them, so they never need clearing
+    let mut slots = vec![Bitboard::EMPTY; 1 << bits];
+    let mut filled_by = vec![0_u32; 1 << bits];
+    let mut attempt = 0;
+    loop {
+        let magic = random.next() & random.next() & random.next();
+        // Good magics spread the mask bits into the top byte
+        if (mask.0.wrapping_mul(magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+
+        attempt += 1;
+        let candidate = Magic { mask, magic, shift, offset: 0 };
+        let collides = occupancies.iter().zip(&attacks).any(|(&occupancy, &attack)| {
+            let index = candidate.index(occupancy);
+            if filled_by[index] != attempt {
+                filled_by[index] = attempt;
+                slots[index] = attack;
+                false
+            } else {
+                slots[index] != attack
+            }
+        });
+        if !collides {
+            let offset = table.len();
+            table.extend(slots.iter().zip(&filled_by).map(|(&slot, &filled)| {
+                if filled == attempt { slot } else { Bitboard::EMPTY }
+            }));
+            return Magic { offset, ..candidate };
+        }
+    }
+}
+
+// Small deterministic generator for the magic search, so the tables come out the same every run
+pub(crate) struct Xorshift(pub(crate) u64);
+
+impl Xorshift {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movegen::rays;
+
+    #[test]
+    fn relevant_occupancy_skips_edges() {
+        assert_eq!(relevant_occupancy(Square::A1, &ROOK_DIRECTIONS).popcount(), 12);
+        assert_eq!(relevant_occupancy(Square::E4, &ROOK_DIRECTIONS).popcount(), 10);
+        assert_eq!(relevant_occupancy(Square::A1, &BISHOP_DIRECTIONS).popcount(), 6);
+        assert_eq!(relevant_occupancy(Square::E4, &BISHOP_DIRECTIONS).popcount(), 9);
+        assert!(!relevant_occupancy(Square::A1, &ROOK_DIRECTIONS).contains(Square::A8));
+    }
+
+    #[test]
+    fn magic_attacks_match_rays() {
+        let magics = MagicAttacks::init();
+        let rays = rays();
+        let mut random = Xorshift(0x9E37_79B9_7F4A_7C15);
+        for i in 0..300 {
+            // Dense, medium and sparse boards
+            let occupancy = match i % 3 {
+                0 => Bitboard(random.next()),
+                1 => Bitboard(random.next() & random.next()),
+                _ => Bitboard(random.next() & random.next() & random.next()),
+            };
+            for square in 0..64 {
+                assert_eq!(magics.bishop_attacks(square, occupancy), rays.bishop_attacks(square, occupancy), "bishop on {}", square);
+                assert_eq!(magics.rook_attacks(square, occupancy), rays.rook_attacks(square, occupancy), "rook on {}", square);
+                assert_eq!(magics.queen_attacks(square, occupancy), rays.queen_attacks(square, occupancy), "queen on {}", square);
+            }
+        }
+    }
+}
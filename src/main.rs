@@ -1,19 +1,81 @@
-pub mod utils;
-pub mod game;
-pub mod rayattacks;
-pub mod knightattacks;
-use crate::game::*;
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use rust_chess_engine::fen::START_FEN;
+use rust_chess_engine::game::*;
+use rust_chess_engine::perft::{divide, perft};
+
+fn usage() -> ! {
+    eprintln!("Usage: rust_chess_engine perft <depth> [fen] | divide <depth> [fen]");
+    process::exit(1);
+}
+
+// Parses the `<depth> [fen]` arguments shared by perft and divide.
+// The FEN may be passed quoted or as separate arguments and defaults to the start position.
+fn depth_and_game(args: &[String]) -> (usize, Game) {
+    let depth = match args.first().map(|depth| depth.parse()) {
+        Some(Ok(depth)) => depth,
+        _ => usage(),
+    };
+    let fenstr = if args.len() > 1 {
+        args[1..].join(" ")
+    } else {
+        START_FEN.to_string()
+    };
+    (depth, read_fen_or_exit(&fenstr))
+}
+
+fn read_fen_or_exit(fenstr: &str) -> Game {
+    match Game::read_fen(fenstr) {
+        Ok(game) => game,
+        Err(err) => {
+            eprintln!("Invalid FEN '{}': {}", fenstr, err);
+            process::exit(1);
+        }
+    }
+}
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|command| command.as_str()) {
+        Some("perft") => {
+            let (depth, mut game) = depth_and_game(&args[1..]);
+            let start = Instant::now();
+            let nodes = perft(&mut game, depth);
+            println!("Nodes: {}", nodes);
+            println!("Time: {:?}", start.elapsed());
+            return;
+        }
+        Some("divide") => {
+            let (depth, mut game) = depth_and_game(&args[1..]);
+            let split = divide(&mut game, depth);
+            for (mv, nodes) in &split {
+                println!("{}: {}", mv, nodes);
+            }
+            println!();
+            println!("Moves: {}", split.len());
+            println!("Nodes: {}", split.iter().map(|(_, nodes)| nodes).sum::<u64>());
+            return;
+        }
+        Some(_) => usage(),
+        None => (),
+    }
+
     // let game = Game::init();
     // let fenstr = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-    let fenstr = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"; 
-    let game = Game::read_fen(&fenstr);
-    println!("{}",game.board_rep());
+    let fenstr = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+    let game = read_fen_or_exit(fenstr);
+    let position = &game.position;
+    println!("{}",position.board_rep());
     println!("Raw FEN: {}", fenstr);
-    println!("Active Color: {:?}", game.active_color); 
-    println!("Castling Rights: {:?},  {:04b}", game.castling_rights, game.castling_rights.bits()); 
-    println!("En Passant Square: {:?}", game.en_passant);
-    println!("Ply: {:?}", game.ply); 
-    println!("Full Moves: {:?}", game.fullmoves); 
+    println!("FEN: {}", position.to_fen());
+    println!("Active Color: {:?}", position.active_color());
+    println!("Castling Rights: {:?},  {:04b}", position.castling_rights(), position.castling_rights().bits());
+    println!("En Passant Square: {:?}", position.en_passant());
+    println!("Ply: {:?}", position.ply());
+    println!("Full Moves: {:?}", position.fullmoves());
+    let moves: Vec<String> = position.legal_moves().iter().map(|mv| mv.to_string()).collect();
+    println!("Legal Moves ({}): {}", moves.len(), moves.join(" "));
 }
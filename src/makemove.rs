@@ -0,0 +1,248 @@
+use crate::game::*;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::square::Square;
+use crate::zobrist::ZOBRIST;
+
+// Everything make_move overwrites that cannot be recomputed from the move itself
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct UndoState {
+    pub(crate) mv: Move,
+    captured: Option<PieceType>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    ply: u16,
+    key: u64,
+}
+
+// Castling rights lost when a move starts or ends on the given square
+fn castling_rights_lost(square: usize) -> CastlingRights {
+    match square {
+        0 => CastlingRights::WHITEQUEENSIDE,
+        4 => CastlingRights::WHITEKINGSIDE | CastlingRights::WHITEQUEENSIDE,
+        7 => CastlingRights::WHITEKINGSIDE,
+        56 => CastlingRights::BLACKQUEENSIDE,
+        60 => CastlingRights::BLACKKINGSIDE | CastlingRights::BLACKQUEENSIDE,
+        63 => CastlingRights::BLACKKINGSIDE,
+        _ => CastlingRights::NONE,
+    }
+}
+
+// Rook squares (from, to) for a castling king landing on `king_to`
+fn castling_rook_squares(king_to: usize) -> (usize, usize) {
+    match king_to {
+        6 => (7, 5),
+        2 => (0, 3),
+        62 => (63, 61),
+        58 => (56, 59),
+        other => panic!("Not a castling destination: {}", index_to_position(other)),
+    }
+}
+
+// The square of the piece `mv` captures when played by `color`, which is
+// behind the target square for en passant
+fn captured_square(mv: Move, color: Color) -> usize {
+    match (mv.is_en_passant(), color) {
+        (false, _) => mv.to(),
+        (true, Color::White) => mv.to() - 8,
+        (true, Color::Black) => mv.to() + 8,
+    }
+}
+
+impl Position {
+    // Plays `mv` on this position. The move is expected to come from the move generator.
+    // Game::make_move records what this overwrites on its undo stack first, so
+    // Game::unmake_move can take the move back.
+    pub fn make_move(&mut self, mv: Move) {
+        let (from, to) = (mv.from(), mv.to());
+        let (color, piece_type) = match self.piece_at(from) {
+            Some(piece) => piece,
+            None => panic!("No piece to move on {}", index_to_position(from)),
+        };
+
+        // The key loses what the move takes away and gains what it puts in place
+        let mut key = self.key ^ ZOBRIST.castling(self.castling_rights) ^ ZOBRIST.en_passant(self.hashed_en_passant());
+
+        let captured = self.board.remove_piece(captured_square(mv, color));
+        if let Some((captured_color, captured_type)) = captured {
+            key ^= ZOBRIST.piece(captured_color, captured_type, captured_square(mv, color));
+        }
+        self.board.remove_piece(from);
+        let placed = mv.promotion().unwrap_or(piece_type);
+        self.board.put_piece(to, color, placed);
+        key ^= ZOBRIST.piece(color, piece_type, from) ^ ZOBRIST.piece(color, placed, to);
+        if mv.is_castling() {
+            let (rook_from, rook_to) = castling_rook_squares(to);
+            self.board.move_piece(rook_from, rook_to);
+            key ^= ZOBRIST.piece(color, PieceType::Rook, rook_from) ^ ZOBRIST.piece(color, PieceType::Rook, rook_to);
+        }
+
+        self.castling_rights &= !(castling_rights_lost(from) | castling_rights_lost(to));
+        self.active_color = color.opposite();
+        self.en_passant = if mv.is_double_push() {
+            Square::from_index((from + to) / 2)
+        } else {
+            None
+        };
+        self.key = key ^ ZOBRIST.castling(self.castling_rights) ^ ZOBRIST.en_passant(self.hashed_en_passant()) ^ ZOBRIST.black_to_move();
+
+        if piece_type == PieceType::Pawn || captured.is_some() {
+            self.ply = 0;
+        } else {
+            self.ply += 1;
+        }
+        if color == Color::Black {
+            self.fullmoves += 1;
+        }
+
+        // Debug builds check the incremental key on every move, so perft runs catch any desync
+        debug_assert_eq!(self.key, self.compute_zobrist_key(), "Zobrist key out of sync after {}", mv);
+    }
+
+    // What `mv` is about to overwrite when played on this position
+    fn undo_state(&self, mv: Move) -> UndoState {
+        let captured = self.piece_at(captured_square(mv, self.active_color)).map(|(_, piece_type)| piece_type);
+        UndoState {
+            mv,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            ply: self.ply,
+            key: self.key,
+        }
+    }
+
+    // Takes back the move recorded in `undo`, which must be the last one played
+    fn unmake_move(&mut self, undo: &UndoState) {
+        let mv = undo.mv;
+        let (from, to) = (mv.from(), mv.to());
+        self.active_color = self.active_color.opposite();
+        let color = self.active_color;
+        if color == Color::Black {
+            self.fullmoves -= 1;
+        }
+
+        if mv.is_castling() {
+            let (rook_from, rook_to) = castling_rook_squares(to);
+            self.board.move_piece(rook_to, rook_from);
+        }
+        let moved = match (mv.promotion(), self.board.remove_piece(to)) {
+            (Some(_), _) => PieceType::Pawn,
+            (None, Some((_, piece_type))) => piece_type,
+            (None, None) => panic!("No piece to take back on {}", index_to_position(to)),
+        };
+        self.board.put_piece(from, color, moved);
+        if let Some(captured) = undo.captured {
+            self.board.put_piece(captured_square(mv, color), color.opposite(), captured);
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.ply = undo.ply;
+        self.key = undo.key;
+    }
+}
+
+impl Game {
+    // Plays `mv`, remembering the state it destroys so unmake_move can restore it exactly
+    pub fn make_move(&mut self, mv: Move) {
+        self.history.push(self.position.undo_state(mv));
+        self.position.make_move(mv);
+    }
+
+    // Takes back the last move played with make_move. Returns the move, or None
+    // when there is nothing to undo.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        self.position.unmake_move(&undo);
+        let mv = undo.mv;
+        debug_assert_eq!(self.position.key, self.position.compute_zobrist_key(), "Zobrist key out of sync undoing {}", mv);
+        Some(mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perft::perft;
+
+    fn count_leaves(game: &mut Game, depth: usize) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        let mut nodes = 0;
+        for mv in game.legal_moves() {
+            game.make_move(mv);
+            nodes += count_leaves(game, depth - 1);
+            game.unmake_move();
+        }
+        nodes
+    }
+
+    #[test]
+    fn unmake_restores_every_move_exactly() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ];
+        for fen in fens {
+            let mut game = Game::read_fen(fen).unwrap();
+            let before = game.position;
+            for mv in before.legal_moves() {
+                game.make_move(mv);
+                assert_ne!(game.position, before);
+                assert_eq!(game.unmake_move(), Some(mv));
+                assert_eq!(game.position, before, "{} after {}", fen, mv);
+                assert_eq!((game.position.ply, game.position.fullmoves), (before.ply, before.fullmoves));
+                assert_eq!(game.position.zobrist_key(), before.zobrist_key());
+            }
+        }
+    }
+
+    #[test]
+    fn make_move_updates_irreversible_state() {
+        let mut game = Game::read_fen("r3k2r/8/8/8/8/8/6p1/R3K2R b KQkq - 5 10").unwrap();
+        let capture = game.legal_moves().into_iter()
+            .find(|mv| mv.to_string() == "g2h1q")
+            .unwrap();
+        game.make_move(capture);
+        assert_eq!(game.position.castling_rights, CastlingRights::WHITEQUEENSIDE | CastlingRights::BLACKKINGSIDE | CastlingRights::BLACKQUEENSIDE);
+        assert_eq!(game.position.ply, 0);
+        assert_eq!(game.position.fullmoves, 11);
+        assert_eq!(game.position.active_color, Color::White);
+        assert_eq!(game.piece_at(7).map(|(_, piece_type)| piece_type), Some(PieceType::Queen));
+
+        let king_move = game.legal_moves().into_iter()
+            .find(|mv| mv.to_string() == "e1d2")
+            .unwrap();
+        game.make_move(king_move);
+        assert_eq!(game.position.castling_rights, CastlingRights::BLACKKINGSIDE | CastlingRights::BLACKQUEENSIDE);
+        assert_eq!(game.position.ply, 1);
+
+        let castle = game.legal_moves().into_iter()
+            .find(|mv| mv.is_castling() && mv.to() == 58)
+            .unwrap();
+        game.make_move(castle);
+        assert_eq!(game.piece_at(59).map(|(_, piece_type)| piece_type), Some(PieceType::Rook));
+        assert_eq!(game.position.castling_rights, CastlingRights::NONE);
+
+        game.unmake_move();
+        game.unmake_move();
+        game.unmake_move();
+        assert_eq!(game.piece_at(14).map(|(_, piece_type)| piece_type), Some(PieceType::Pawn));
+        assert_eq!(game.position.castling_rights, CastlingRights::ALL);
+        assert_eq!((game.position.ply, game.position.fullmoves), (5, 10));
+        assert!(game.unmake_move().is_none());
+    }
+
+    #[test]
+    fn shallow_move_counts() {
+        let mut game = Game::read_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(count_leaves(&mut game, 3), 8902);
+        let mut game = Game::read_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(perft(&mut game, 2), 2039);
+        let mut game = Game::read_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+        assert_eq!(perft(&mut game, 2), 1486);
+    }
+}
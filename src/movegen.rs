@@ -0,0 +1,408 @@
+use std::sync::OnceLock;
+
+use crate::attacks::{king_attacks, knight_attacks, pawn_attacks, pawn_double_pushes, pawn_pushes};
+use crate::bitboard::Bitboard;
+use crate::game::*;
+use crate::position::Position;
+use crate::moves::{Move, MoveFlags};
+use crate::rayattacks::Rays;
+use crate::sliders::{SliderAttacks, Sliders};
+use crate::square::Square;
+
+const PROMOTION_PIECES: [PieceType; 4] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+// The slider backend is only built once and then shared by every Game.
+// Rays and the step tables live in crate::attacks and are built at compile time.
+static RAYS: Rays = Rays::init();
+static SLIDERS: OnceLock<Sliders> = OnceLock::new();
+
+pub fn rays() -> &'static Rays {
+    &RAYS
+}
+
+// The fastest slider backend available, see Sliders::detect
+pub fn sliders() -> &'static Sliders {
+    SLIDERS.get_or_init(Sliders::detect)
+}
+
+// Squares strictly between two squares on a shared rank, file or diagonal, empty otherwise
+fn between(a: usize, b: usize) -> Bitboard {
+    let sliders = sliders();
+    let (a_bit, b_bit) = (Bitboard::from_index(a), Bitboard::from_index(b));
+    if !(sliders.rook_attacks(a, Bitboard::EMPTY) & b_bit).is_empty() {
+        sliders.rook_attacks(a, b_bit) & sliders.rook_attacks(b, a_bit)
+    } else if !(sliders.bishop_attacks(a, Bitboard::EMPTY) & b_bit).is_empty() {
+        sliders.bishop_attacks(a, b_bit) & sliders.bishop_attacks(b, a_bit)
+    } else {
+        Bitboard::EMPTY
+    }
+}
+
+// Pushes one move per set bit of `targets`, flagging the ones landing on an enemy piece
+fn push_moves(from: usize, targets: Bitboard, enemy: Bitboard, moves: &mut Vec<Move>) {
+    for to in targets {
+        let flags = if enemy.contains(to) { MoveFlags::CAPTURE } else { MoveFlags::NONE };
+        moves.push(Move::new(from, to.index(), flags));
+    }
+}
+
+// Pushes a pawn move, expanding it into the four promotions on the last rank
+fn push_pawn_move(from: usize, to: usize, flags: MoveFlags, moves: &mut Vec<Move>) {
+    if !(8..56).contains(&to) {
+        for piece_type in PROMOTION_PIECES {
+            moves.push(Move::promoting(from, to, piece_type, flags));
+        }
+    } else {
+        moves.push(Move::new(from, to, flags));
+    }
+}
+
+impl Position {
+    // Every move for the active color that follows the piece movement rules,
+    // without checking whether it leaves the own king in check.
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let own = self.color_occupancy(self.active_color);
+        let enemy = self.color_occupancy(self.active_color.opposite());
+        let occupancy = own | enemy;
+
+        for piece_type in PieceType::ALL {
+            for from in self.piece_bitboard(self.active_color, piece_type) {
+                let from = from.index();
+                match piece_type {
+                    PieceType::Pawn => self.pawn_moves(from, enemy, occupancy, &mut moves),
+                    PieceType::Knight => push_moves(from, knight_attacks(from) & !own, enemy, &mut moves),
+                    PieceType::Bishop => push_moves(from, sliders().bishop_attacks(from, occupancy) & !own, enemy, &mut moves),
+                    PieceType::Rook => push_moves(from, sliders().rook_attacks(from, occupancy) & !own, enemy, &mut moves),
+                    PieceType::Queen => push_moves(from, sliders().queen_attacks(from, occupancy) & !own, enemy, &mut moves),
+                    PieceType::King => {
+                        push_moves(from, king_attacks(from) & !own, enemy, &mut moves);
+                        self.castling_moves(from, occupancy, &mut moves);
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    // Every move for the active color that does not leave the own king in check.
+    // Pseudo-legal moves are filtered with a check mask (the squares that resolve
+    // a single check) and per-piece pin masks instead of playing each move out.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let us = self.active_color;
+        let them = us.opposite();
+        let king = self.piece_bitboard(us, PieceType::King);
+        let Some(king_square) = king.lsb() else {
+            return self.pseudo_legal_moves();
+        };
+        let king_square = king_square.index();
+        let own = self.color_occupancy(us);
+        let enemy = self.color_occupancy(them);
+        let occupancy = own | enemy;
+
+        let checkers = self.attackers(king_square, them, occupancy);
+        // The king is removed so it cannot step back along the ray of a checking slider
+        let attacked = self.attacked_squares(them, occupancy ^ king);
+
+        let check_mask = match checkers.lsb() {
+            None => Bitboard::FULL,
+            Some(checker) if checkers.popcount() == 1 => checkers | between(king_square, checker.index()),
+            Some(_) => Bitboard::EMPTY,
+        };
+        let pins = self.pin_masks(king_square, own, enemy);
+
+        self.pseudo_legal_moves().into_iter().filter(|mv| {
+            let from_bit = Bitboard::from_index(mv.from());
+            let to_bit = Bitboard::from_index(mv.to());
+
+            if from_bit == king {
+                if mv.is_castling() {
+                    // Castling: not out of, through or into check
+                    let crossed = Bitboard::from_index((mv.from() + mv.to()) / 2);
+                    return checkers.is_empty() && (attacked & (to_bit | crossed)).is_empty();
+                }
+                return (attacked & to_bit).is_empty();
+            }
+
+            if mv.is_en_passant() {
+                let captured = match us {
+                    Color::White => to_bit.south(),
+                    Color::Black => to_bit.north(),
+                };
+                if (check_mask & (to_bit | captured)).is_empty() {
+                    return false;
+                }
+                // Both pawns leave their squares at once, which can uncover a slider
+                // along the rank, so the king is checked against the resulting board
+                let after = (occupancy ^ from_bit ^ captured) | to_bit;
+                return (self.slider_attackers(king_square, them, after) & !captured).is_empty();
+            }
+
+            !(check_mask & to_bit & pins[mv.from()]).is_empty()
+        }).collect()
+    }
+
+    // For each square, the squares its piece may move to without breaking a pin
+    // against the own king. Unpinned squares allow every target.
+    fn pin_masks(&self, king_square: usize, own: Bitboard, enemy: Bitboard) -> [Bitboard; 64] {
+        let them = self.active_color.opposite();
+        let queens = self.piece_bitboard(them, PieceType::Queen);
+        let rooks = self.piece_bitboard(them, PieceType::Rook) | queens;
+        let bishops = self.piece_bitboard(them, PieceType::Bishop) | queens;
+
+        // Looking from the king through the own pieces finds the enemy sliders that could pin
+        let pinners = (sliders().rook_attacks(king_square, enemy) & rooks)
+            | (sliders().bishop_attacks(king_square, enemy) & bishops);
+
+        let mut masks = [Bitboard::FULL; 64];
+        for pinner in pinners {
+            let line = between(king_square, pinner.index());
+            let blockers = line & own;
+            if let Some(pinned) = blockers.lsb()
+                && blockers.popcount() == 1 {
+                masks[pinned.index()] = line | pinner.bitboard();
+            }
+        }
+        masks
+    }
+
+    // Pieces of `color` attacking `square` with the given occupancy
+    pub(crate) fn attackers(&self, square: usize, color: Color, occupancy: Bitboard) -> Bitboard {
+        (knight_attacks(square) & self.piece_bitboard(color, PieceType::Knight))
+            | (king_attacks(square) & self.piece_bitboard(color, PieceType::King))
+            | (pawn_attacks(square, color.opposite()) & self.piece_bitboard(color, PieceType::Pawn))
+            | self.slider_attackers(square, color, occupancy)
+    }
+
+    fn slider_attackers(&self, square: usize, color: Color, occupancy: Bitboard) -> Bitboard {
+        let queens = self.piece_bitboard(color, PieceType::Queen);
+        (sliders().rook_attacks(square, occupancy) & (self.piece_bitboard(color, PieceType::Rook) | queens))
+            | (sliders().bishop_attacks(square, occupancy) & (self.piece_bitboard(color, PieceType::Bishop) | queens))
+    }
+
+    // Union of every square attacked by a piece of `color`
+    fn attacked_squares(&self, color: Color, occupancy: Bitboard) -> Bitboard {
+        let mut attacked = Bitboard::EMPTY;
+        for piece_type in PieceType::ALL {
+            for from in self.piece_bitboard(color, piece_type) {
+                let from = from.index();
+                attacked |= match piece_type {
+                    PieceType::Pawn => pawn_attacks(from, color),
+                    PieceType::Knight => knight_attacks(from),
+                    PieceType::Bishop => sliders().bishop_attacks(from, occupancy),
+                    PieceType::Rook => sliders().rook_attacks(from, occupancy),
+                    PieceType::Queen => sliders().queen_attacks(from, occupancy),
+                    PieceType::King => king_attacks(from),
+                };
+            }
+        }
+        attacked
+    }
+
+    // Whether any piece of `by_color` attacks `square` on the current board
+    pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
+        !self.attackers(square.index(), by_color, self.occupancy()).is_empty()
+    }
+
+    // Pieces of either color attacking `square` if the board held `occupancy`.
+    // Passing an occupancy with pieces removed shows x-ray attackers behind them.
+    pub fn attackers_to(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        (self.attackers(square.index(), Color::White, occupancy) | self.attackers(square.index(), Color::Black, occupancy))
+            & occupancy
+    }
+
+    // Whether the king of the side to move is attacked
+    pub fn in_check(&self) -> bool {
+        self.piece_bitboard(self.active_color, PieceType::King).lsb()
+            .is_some_and(|king| self.is_square_attacked(king, self.active_color.opposite()))
+    }
+
+    // Every square attacked by a piece of `color`, including squares holding own pieces
+    pub fn attack_map(&self, color: Color) -> Bitboard {
+        self.attacked_squares(color, self.occupancy())
+    }
+
+    fn pawn_moves(&self, from: usize, enemy: Bitboard, occupancy: Bitboard, moves: &mut Vec<Move>) {
+        let single = pawn_pushes(from, self.active_color);
+        let double = pawn_double_pushes(from, self.active_color);
+
+        if let Some(to) = single.lsb()
+            && (single & occupancy).is_empty() {
+            push_pawn_move(from, to.index(), MoveFlags::NONE, moves);
+            if let Some(to) = double.lsb()
+                && (double & occupancy).is_empty() {
+                moves.push(Move::new(from, to.index(), MoveFlags::DOUBLE_PUSH));
+            }
+        }
+
+        let attacks = pawn_attacks(from, self.active_color);
+        for to in attacks & enemy {
+            push_pawn_move(from, to.index(), MoveFlags::CAPTURE, moves);
+        }
+
+        if let Some(en_passant) = self.en_passant
+            && let Some(to) = (attacks & en_passant.bitboard()).lsb() {
+            moves.push(Move::new(from, to.index(), MoveFlags::CAPTURE | MoveFlags::EN_PASSANT));
+        }
+    }
+
+    // Castling only requires the right and empty squares between king and rook here;
+    // whether the king passes through check is left to legal move generation.
+    fn castling_moves(&self, from: usize, occupancy: Bitboard, moves: &mut Vec<Move>) {
+        let (kingside, queenside, king_square) = match self.active_color {
+            Color::White => (CastlingRights::WHITEKINGSIDE, CastlingRights::WHITEQUEENSIDE, 4),
+            Color::Black => (CastlingRights::BLACKKINGSIDE, CastlingRights::BLACKQUEENSIDE, 60),
+        };
+        if from != king_square {
+            return;
+        }
+
+        let own_rook_on = |index: usize| self.piece_at(index) == Some((self.active_color, PieceType::Rook));
+
+        if self.castling_rights.contains(kingside)
+            && (occupancy & Bitboard(0b11 << (from + 1))).is_empty()
+            && own_rook_on(from + 3) {
+            moves.push(Move::new(from, from + 2, MoveFlags::CASTLING));
+        }
+        if self.castling_rights.contains(queenside)
+            && (occupancy & Bitboard(0b111 << (from - 3))).is_empty()
+            && own_rook_on(from - 4) {
+            moves.push(Move::new(from, from - 2, MoveFlags::CASTLING));
+        }
+    }
+}
+
+// Move generation and attack queries on the current position of a Game
+impl Game {
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        self.position.pseudo_legal_moves()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.position.legal_moves()
+    }
+
+    pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
+        self.position.is_square_attacked(square, by_color)
+    }
+
+    pub fn attackers_to(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        self.position.attackers_to(square, occupancy)
+    }
+
+    pub fn attack_map(&self, color: Color) -> Bitboard {
+        self.position.attack_map(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_position_has_twenty_moves() {
+        let position = Position::read_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(position.pseudo_legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn kiwipete_moves() {
+        let position = Position::read_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let moves = position.pseudo_legal_moves();
+        assert_eq!(moves.len(), 48);
+        assert!(moves.contains(&Move::new(4, 6, MoveFlags::CASTLING)));
+        assert!(moves.contains(&Move::new(4, 2, MoveFlags::CASTLING)));
+        assert_eq!(moves.iter().filter(|mv| mv.is_capture()).count(), 8);
+    }
+
+    #[test]
+    fn black_en_passant_and_promotions() {
+        let position = Position::read_fen("4k3/8/8/8/3pP3/8/6p1/4K2R b K e3 0 1").unwrap();
+        let moves = position.pseudo_legal_moves();
+        assert!(moves.contains(&Move::new(27, 20, MoveFlags::CAPTURE | MoveFlags::EN_PASSANT)));
+        assert_eq!(moves.iter().filter(|mv| mv.promotion().is_some()).count(), 8);
+    }
+
+    #[test]
+    fn legal_move_counts_for_reference_positions() {
+        let positions = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 20),
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 48),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 14),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 6),
+            ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 44),
+            ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", 46),
+        ];
+        for (fen, expected) in positions {
+            assert_eq!(Position::read_fen(fen).unwrap().legal_moves().len(), expected, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn double_check_allows_only_king_moves() {
+        // Rook on e8 and knight on f3 both give check
+        let position = Position::read_fen("4r2k/8/8/8/8/5n2/8/3QK3 w - - 0 1").unwrap();
+        let moves = position.legal_moves();
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|mv| mv.from() == 4));
+    }
+
+    #[test]
+    fn en_passant_cannot_uncover_rank_check() {
+        let position = Position::read_fen("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1").unwrap();
+        let en_passant = Move::new(36, 43, MoveFlags::CAPTURE | MoveFlags::EN_PASSANT);
+        assert!(!position.legal_moves().contains(&en_passant));
+        let position = Position::read_fen("8/8/8/K2pP3/8/8/8/7k w - d6 0 1").unwrap();
+        assert!(position.legal_moves().contains(&en_passant));
+    }
+
+    #[test]
+    fn castling_through_attacked_square_is_illegal() {
+        // The bishop on a6 covers f1, the rook on d8 covers d1
+        let position = Position::read_fen("3rk3/8/b7/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let moves = position.legal_moves();
+        assert!(!moves.contains(&Move::new(4, 6, MoveFlags::CASTLING)));
+        assert!(!moves.contains(&Move::new(4, 2, MoveFlags::CASTLING)));
+        // b1 may be attacked, the king never crosses it
+        let position = Position::read_fen("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let moves = position.legal_moves();
+        assert!(moves.contains(&Move::new(4, 6, MoveFlags::CASTLING)));
+        assert!(moves.contains(&Move::new(4, 2, MoveFlags::CASTLING)));
+    }
+
+    #[test]
+    fn pinned_piece_stays_on_pin_line() {
+        // The bishop on d2 is pinned by the bishop on a5 against the king on e1
+        let position = Position::read_fen("4k3/8/8/b7/8/8/3B4/4K3 w - - 0 1").unwrap();
+        let bishop_moves: Vec<Move> = position.legal_moves().into_iter().filter(|mv| mv.from() == 11).collect();
+        assert_eq!(bishop_moves.len(), 3);
+    }
+
+    #[test]
+    fn attack_queries() {
+        // White: Kg1, Rd1, Bb3, Nf3, pawn e4. Black: Ke8, Qd8, Nc6, pawn d5.
+        let game = Game::read_fen("3qk3/8/2n5/3p4/4P3/1B3N2/8/3R2K1 w - - 0 1").unwrap();
+        assert!(game.is_square_attacked(Square::D5, Color::White));
+        assert!(game.is_square_attacked(Square::E4, Color::Black));
+        assert!(!game.is_square_attacked(Square::H8, Color::White));
+
+        // d5 is attacked by the e4 pawn, the b3 bishop and the d1 rook and defended by the d8 queen.
+        // The knight on c6 does not reach it.
+        let occupancy = game.position.occupancy();
+        let expected: Bitboard = [Square::E4, Square::B3, Square::D1, Square::D8].into_iter().collect();
+        assert_eq!(game.attackers_to(Square::D5, occupancy), expected);
+        // Lifting the d5 pawn lets the rook and queen see through to each other
+        let lifted = occupancy ^ Square::D5.bitboard();
+        assert!(game.attackers_to(Square::D8, lifted).contains(Square::D1));
+        assert!(!game.attackers_to(Square::D8, occupancy).contains(Square::D1));
+
+        let white = game.attack_map(Color::White);
+        assert!(white.contains(Square::D5));
+        assert!(white.contains(Square::F1));
+        assert!(!white.contains(Square::A8));
+        let black = game.attack_map(Color::Black);
+        assert!(black.contains(Square::E4));
+        assert!(black.contains(Square::B4));
+        assert!(!black.contains(Square::H1));
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use bitflags::bitflags;
+use crate::game::{index_to_position, PieceType};
+
+bitflags! {
+    /// Properties of a move that cannot be told from its squares alone
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    pub struct MoveFlags: u8 {
+        const NONE        = 0;
+        const CAPTURE     = 1 << 0; //0b0001
+        const DOUBLE_PUSH = 1 << 1; //0b0010
+        const EN_PASSANT  = 1 << 2; //0b0100
+        const CASTLING    = 1 << 3; //0b1000
+    }
+}
+
+const SQUARE_MASK: u32 = 0b11_1111;
+const TO_SHIFT: u32 = 6;
+const PROMOTION_SHIFT: u32 = 12;
+const PROMOTION_MASK: u32 = 0b111;
+const FLAGS_SHIFT: u32 = 16;
+
+// A move packed into 32 bits:
+//   bits  0..6   from square index (as used by index_to_position)
+//   bits  6..12  to square index
+//   bits 12..15  promotion piece, 0 when the move is no promotion
+//   bits 16..20  MoveFlags
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Move(u32);
+
+impl Move {
+    pub fn new(from: usize, to: usize, flags: MoveFlags) -> Move {
+        Move(from as u32 | (to as u32) << TO_SHIFT | (flags.bits() as u32) << FLAGS_SHIFT)
+    }
+
+    // A promotion to `promotion`, None for the piece types a pawn cannot become
+    pub fn with_promotion(from: usize, to: usize, promotion: PieceType, flags: MoveFlags) -> Option<Move> {
+        let promotion_bits = match promotion {
+            PieceType::Knight => 1,
+            PieceType::Bishop => 2,
+            PieceType::Rook => 3,
+            PieceType::Queen => 4,
+            PieceType::Pawn | PieceType::King => return None,
+        };
+        Some(Move(Move::new(from, to, flags).0 | promotion_bits << PROMOTION_SHIFT))
+    }
+
+    // For the move generator, which only ever promotes to knight, bishop, rook or queen
+    pub(crate) fn promoting(from: usize, to: usize, promotion: PieceType, flags: MoveFlags) -> Move {
+        Move::with_promotion(from, to, promotion, flags).expect("pawns only promote to a knight, bishop, rook or queen")
+    }
+
+    pub fn from(&self) -> usize {
+        (self.0 & SQUARE_MASK) as usize
+    }
+
+    pub fn to(&self) -> usize {
+        (self.0 >> TO_SHIFT & SQUARE_MASK) as usize
+    }
+
+    pub fn promotion(&self) -> Option<PieceType> {
+        match self.0 >> PROMOTION_SHIFT & PROMOTION_MASK {
+            1 => Some(PieceType::Knight),
+            2 => Some(PieceType::Bishop),
+            3 => Some(PieceType::Rook),
+            4 => Some(PieceType::Queen),
+            _ => None,
+        }
+    }
+
+    pub fn flags(&self) -> MoveFlags {
+        MoveFlags::from_bits_truncate((self.0 >> FLAGS_SHIFT) as u8)
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.flags().contains(MoveFlags::CAPTURE)
+    }
+
+    pub fn is_double_push(&self) -> bool {
+        self.flags().contains(MoveFlags::DOUBLE_PUSH)
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags().contains(MoveFlags::EN_PASSANT)
+    }
+
+    pub fn is_castling(&self) -> bool {
+        self.flags().contains(MoveFlags::CASTLING)
+    }
+
+    // The packed representation
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+// UCI long algebraic notation, e.g. e2e4, e1g1 or e7e8q
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", index_to_position(self.from()), index_to_position(self.to()))?;
+        match self.promotion() {
+            Some(PieceType::Knight) => write!(f, "n"),
+            Some(PieceType::Bishop) => write!(f, "b"),
+            Some(PieceType::Rook) => write!(f, "r"),
+            Some(PieceType::Queen) => write!(f, "q"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_round_trip() {
+        for from in 0..64 {
+            for to in 0..64 {
+                let mv = Move::new(from, to, MoveFlags::CAPTURE | MoveFlags::EN_PASSANT);
+                assert_eq!(mv.from(), from);
+                assert_eq!(mv.to(), to);
+                assert_eq!(mv.promotion(), None);
+                assert!(mv.is_capture() && mv.is_en_passant());
+                assert!(!mv.is_castling() && !mv.is_double_push());
+            }
+        }
+
+        for piece_type in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+            let mv = Move::with_promotion(52, 61, piece_type, MoveFlags::CAPTURE).unwrap();
+            assert_eq!(mv.promotion(), Some(piece_type));
+            assert_eq!(mv.flags(), MoveFlags::CAPTURE);
+        }
+        assert_eq!(Move::with_promotion(52, 60, PieceType::King, MoveFlags::NONE), None);
+        assert_eq!(Move::with_promotion(52, 60, PieceType::Pawn, MoveFlags::NONE), None);
+    }
+
+    #[test]
+    fn displays_as_uci() {
+        assert_eq!(Move::new(12, 28, MoveFlags::DOUBLE_PUSH).to_string(), "e2e4");
+        assert_eq!(Move::new(4, 6, MoveFlags::CASTLING).to_string(), "e1g1");
+        assert_eq!(Move::with_promotion(52, 60, PieceType::Queen, MoveFlags::NONE).unwrap().to_string(), "e7e8q");
+        assert_eq!(Move::with_promotion(9, 0, PieceType::Knight, MoveFlags::CAPTURE).unwrap().to_string(), "b2a1n");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::attacks::{PAWN_ATTACKS, PAWN_DOUBLE_PUSHES, PAWN_PUSHES};
+use crate::bitboard::Bitboard;
+use crate::game::Color;
+
+// The pawn tables of crate::attacks for one color
+pub struct PawnAttacks {
+    captures: &'static [Bitboard; 64],
+    pushes: &'static [Bitboard; 64],
+    double_pushes: &'static [Bitboard; 64],
+}
+
+impl PawnAttacks {
+    pub const fn init(color: Color) -> Self {
+        let color = color as usize;
+        Self {
+            captures: &PAWN_ATTACKS[color],
+            pushes: &PAWN_PUSHES[color],
+            double_pushes: &PAWN_DOUBLE_PUSHES[color],
+        }
+    }
+
+    // Squares a pawn standing on `square` captures on
+    pub fn attacks(&self, square: usize) -> Bitboard {
+        self.captures[square]
+    }
+
+    // The square a pawn on `square` moves to with a single push
+    pub fn pushes(&self, square: usize) -> Bitboard {
+        self.pushes[square]
+    }
+
+    // The square a pawn on its starting rank reaches with a double push, empty elsewhere
+    pub fn double_pushes(&self, square: usize) -> Bitboard {
+        self.double_pushes[square]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::{File, Rank, Square};
+
+    #[test]
+    fn captures_on_edge_files_do_not_wrap() {
+        for (color, ranks) in [(Color::White, 1), (Color::Black, -1)] {
+            let pawn_attacks = PawnAttacks::init(color);
+            for square in Square::ALL {
+                let attacks = pawn_attacks.attacks(square.index());
+                let expected: Bitboard = [-1, 1].into_iter().filter_map(|files| square.offset(files, ranks)).collect();
+                assert_eq!(attacks, expected, "{:?} pawn on {}", color, square);
+                for target in attacks {
+                    assert_eq!(square.file().index().abs_diff(target.file().index()), 1, "{:?} pawn on {} attacks {}", color, square, target);
+                }
+            }
+        }
+
+        let white = PawnAttacks::init(Color::White);
+        assert_eq!(white.attacks(Square::A2.index()), Square::B3.bitboard());
+        assert_eq!(white.attacks(Square::H2.index()), Square::G3.bitboard());
+        assert_eq!(white.attacks(Square::A8.index()), Bitboard::EMPTY);
+        let black = PawnAttacks::init(Color::Black);
+        assert_eq!(black.attacks(Square::A7.index()), Square::B6.bitboard());
+        assert_eq!(black.attacks(Square::H7.index()), Square::G6.bitboard());
+        assert_eq!(black.attacks(Square::H1.index()), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn pushes_stay_on_the_file() {
+        for (color, ranks, start) in [(Color::White, 1, Rank::Second), (Color::Black, -1, Rank::Seventh)] {
+            let pawn_attacks = PawnAttacks::init(color);
+            for square in Square::ALL {
+                let single = square.offset(0, ranks).map_or(Bitboard::EMPTY, |target| target.bitboard());
+                assert_eq!(pawn_attacks.pushes(square.index()), single, "{:?} pawn on {}", color, square);
+
+                let double = if square.rank() == start {
+                    square.offset(0, 2 * ranks).unwrap().bitboard()
+                } else {
+                    Bitboard::EMPTY
+                };
+                assert_eq!(pawn_attacks.double_pushes(square.index()), double, "{:?} pawn on {}", color, square);
+            }
+        }
+
+        let white = PawnAttacks::init(Color::White);
+        assert_eq!(white.pushes(Square::H7.index()), Square::H8.bitboard());
+        assert_eq!(white.pushes(Square::H8.index()), Bitboard::EMPTY);
+        assert_eq!(white.double_pushes(Square::new(File::A, Rank::Second).index()), Square::A4.bitboard());
+        let black = PawnAttacks::init(Color::Black);
+        assert_eq!(black.pushes(Square::A1.index()), Bitboard::EMPTY);
+        assert_eq!(black.double_pushes(Square::H7.index()), Square::H5.bitboard());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::game::Game;
+use crate::moves::Move;
+
+// Number of leaf nodes of the legal move tree `depth` plies below `game`
+pub fn perft(game: &mut Game, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = game.legal_moves();
+    // The leaves one ply down are just the moves, so there is no need to play them
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        game.make_move(mv);
+        nodes += perft(game, depth - 1);
+        game.unmake_move();
+    }
+    nodes
+}
+
+// Perft split up by root move, in move generation order
+pub fn divide(game: &mut Game, depth: usize) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+
+    let mut results = vec![];
+    for mv in game.legal_moves() {
+        game.make_move(mv);
+        results.push((mv, perft(game, depth - 1)));
+        game.unmake_move();
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_position_totals() {
+        let mut game = Game::read_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(perft(&mut game, 0), 1);
+        assert_eq!(perft(&mut game, 1), 20);
+        assert_eq!(perft(&mut game, 2), 400);
+        assert_eq!(perft(&mut game, 3), 8902);
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let mut game = Game::read_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let split = divide(&mut game, 2);
+        assert_eq!(split.len(), 48);
+        assert_eq!(split.iter().map(|(_, nodes)| nodes).sum::<u64>(), perft(&mut game, 2));
+        let castle = split.iter().find(|(mv, _)| mv.to_string() == "e1g1").unwrap();
+        assert_eq!(castle.1, 43);
+    }
+
+    // Move generation speed in nodes per second, to compare against before changing hot code.
+    // cargo test --release --lib perft_benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore = "benchmark, run with --release -- --ignored --nocapture"]
+    fn perft_benchmark() {
+        let positions = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 5),
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 4),
+        ];
+        println!("slider backend: {}", crate::movegen::sliders().name());
+        for (fen, depth) in positions {
+            let mut game = Game::read_fen(fen).unwrap();
+            // Builds the shared lookup tables before the clock starts
+            perft(&mut game, 1);
+            let start = std::time::Instant::now();
+            let nodes = perft(&mut game, depth);
+            let elapsed = start.elapsed();
+            println!("{} depth {}: {} nodes in {:.2?} ({:.0} nodes/s)", fen, depth, nodes, elapsed, nodes as f64 / elapsed.as_secs_f64());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::arch::x86_64::_pext_u64;
+
+use crate::bitboard::Bitboard;
+use crate::magic::{occupancy_subsets, relevant_occupancy, sliding_attacks, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use crate::square::Square;
+
+// The relevant occupancy of one slider on one square and where its attack sets start in the table
+#[derive(Debug, Default, Copy, Clone)]
+struct PextEntry {
+    mask: Bitboard,
+    offset: usize,
+}
+
+// Slider attacks indexed with the BMI2 `pext` instruction, which packs the occupancy
+// bits under the mask into a dense index. No magic numbers and no multiplication,
+// but the instruction only exists on newer x86-64 CPUs.
+pub struct PextAttacks {
+    bishops: [PextEntry; 64],
+    rooks: [PextEntry; 64],
+    table: Vec<Bitboard>,
+}
+
+impl PextAttacks {
+    // None when the CPU does not support BMI2
+    pub fn init() -> Option<Self> {
+        if !is_x86_feature_detected!("bmi2") {
+            return None;
+        }
+
+        let mut table = vec![];
+        let mut bishops = [PextEntry::default(); 64];
+        let mut rooks = [PextEntry::default(); 64];
+        for square in Square::ALL {
+            bishops[square.index()] = fill_entry(square, &BISHOP_DIRECTIONS, &mut table);
+            rooks[square.index()] = fill_entry(square, &ROOK_DIRECTIONS, &mut table);
+        }
+        Some(Self { bishops, rooks, table })
+    }
+
+    pub fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.lookup(self.bishops[square], occupancy)
+    }
+
+    pub fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.lookup(self.rooks[square], occupancy)
+    }
+
+    pub fn queen_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.bishop_attacks(square, occupancy) | self.rook_attacks(square, occupancy)
+    }
+
+    fn lookup(&self, entry: PextEntry, occupancy: Bitboard) -> Bitboard {
+        // Safe because init only builds a PextAttacks after detecting BMI2
+        let index = unsafe { pext(occupancy.0, entry.mask.0) } as usize;
+        self.table[entry.offset + index]
+    }
+}
+
+#[target_feature(enable = "bmi2")]
+fn pext(value: u64, mask: u64) -> u64 {
+    _pext_u64(value, mask)
+}
+
+// The subsets come out in the order pext numbers them, so the n-th subset's attacks go in slot n
+fn fill_entry(square: Square, directions: &[(i32, i32)], table: &mut Vec<Bitboard>) -> PextEntry {
+    let mask = relevant_occupancy(square, directions);
+    let offset = table.len();
+    table.extend(occupancy_subsets(mask).into_iter().map(|occupancy| sliding_attacks(square, occupancy, directions)));
+    PextEntry { mask, offset }
+}
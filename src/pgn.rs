@@ -0,0 +1,759 @@
+use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+use crate::fen::{FenError, START_FEN};
+use crate::game::*;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::san::SanError;
+
+// One move of a PGN game with the annotations that follow it
+#[derive(Debug, PartialEq, Clone)]
+pub struct PgnMove {
+    pub mv: Move,
+    // Numeric annotation glyphs, with !, ?, !!, ??, !? and ?! read as $1 to $6
+    pub nags: Vec<u8>,
+    // Comments between the move and the next one, without the clock and eval commands
+    pub comments: Vec<String>,
+    // Time left on the mover's clock after the move, from a [%clk] command
+    pub clock: Option<Duration>,
+    // Engine evaluation after the move, from an [%eval] command
+    pub eval: Option<Eval>,
+    // Comments before the first move of a variation
+    pub starting_comments: Vec<String>,
+    // Lines played instead of this move, each starting from the position before it
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    // The move with no annotations
+    pub fn new(mv: Move) -> PgnMove {
+        PgnMove { mv, nags: vec![], comments: vec![], clock: None, eval: None, starting_comments: vec![], variations: vec![] }
+    }
+}
+
+// An [%eval] score from white's point of view
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Eval {
+    Centipawns(i32),
+    // Moves to mate, negative when black mates
+    Mate(i32),
+}
+
+impl fmt::Display for Eval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Eval::Centipawns(centipawns) => {
+                let sign = if *centipawns < 0 { "-" } else { "" };
+                write!(f, "{}{}.{:02}", sign, centipawns.abs() / 100, centipawns.abs() % 100)
+            }
+            Eval::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+impl Eval {
+    // Reads "0.17", "-1.5" or "#-3", ignoring a ",depth" suffix
+    fn parse(text: &str) -> Option<Eval> {
+        let score = text.split(',').next()?.trim();
+        match score.strip_prefix('#') {
+            Some(moves) => moves.parse().ok().map(Eval::Mate),
+            None => {
+                let pawns: f64 = score.parse().ok()?;
+                pawns.is_finite().then(|| Eval::Centipawns((pawns * 100.0).round() as i32))
+            }
+        }
+    }
+}
+
+// Reads a [%clk] time as h:mm:ss with optional fractions of a second
+fn parse_clock(text: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for part in text.trim().split(':') {
+        let value: f64 = part.parse().ok()?;
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Some(Duration::from_millis((seconds * 1000.0).round() as u64))
+}
+
+// Writes a clock as h:mm:ss, with tenths of a second only when there are any
+pub(crate) fn format_clock(clock: Duration) -> String {
+    let seconds = clock.as_secs();
+    let tenths = clock.subsec_millis() / 100;
+    let time = format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if tenths == 0 { time } else { format!("{}.{}", time, tenths) }
+}
+
+// Moves the [%clk] and [%eval] commands of a comment onto the move and returns the
+// text that is left. Other commands stay in the text.
+fn take_commands(comment: &str, played: &mut PgnMove) -> String {
+    let mut rest = String::new();
+    let mut text = comment;
+    while let Some(start) = text.find("[%") {
+        let Some(length) = text[start..].find(']') else { break };
+        let command = &text[start + 2..start + length];
+        let (name, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let taken = match name {
+            "clk" => parse_clock(argument).map(|clock| played.clock = Some(clock)),
+            "eval" => Eval::parse(argument).map(|eval| played.eval = Some(eval)),
+            _ => None,
+        };
+        rest.push_str(&text[..start]);
+        if taken.is_none() {
+            rest.push_str(&text[start..=start + length]);
+        }
+        text = &text[start + length + 1..];
+    }
+    rest.push_str(text);
+    rest.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// A game read from PGN. `game` has the mainline played out on top of the start position.
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    // Tags in the order they were read, the seven tag roster included
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub game: Game,
+    pub moves: Vec<PgnMove>,
+    // Comments before the first move of the game
+    pub comments: Vec<String>,
+    // "1-0", "0-1", "1/2-1/2" or "*", None when the movetext ended without one
+    pub result: Option<String>,
+}
+
+impl PgnGame {
+    // The value of the first tag called `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+}
+
+// What went wrong while reading a game
+#[derive(Debug, PartialEq, Clone)]
+pub enum PgnErrorKind {
+    // Reading the underlying input failed; the reader stops after reporting it
+    Io(String),
+    InvalidTag,
+    UnexpectedCharacter(char),
+    InvalidNag(String),
+    UnterminatedComment,
+    InvalidFen(FenError),
+    // A movetext move that is malformed, illegal or ambiguous
+    InvalidMove(SanError),
+    // A variation opened before any move it could replace
+    VariationWithoutMove,
+    UnmatchedParenthesis,
+    UnterminatedVariation,
+}
+
+impl fmt::Display for PgnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnErrorKind::Io(error) => write!(f, "read failed: {}", error),
+            PgnErrorKind::InvalidTag => write!(f, "malformed tag pair"),
+            PgnErrorKind::UnexpectedCharacter(ch) => write!(f, "unexpected character '{}'", ch),
+            PgnErrorKind::InvalidNag(nag) => write!(f, "invalid annotation '{}'", nag),
+            PgnErrorKind::UnterminatedComment => write!(f, "comment is never closed"),
+            PgnErrorKind::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnErrorKind::InvalidMove(error) => write!(f, "{}", error),
+            PgnErrorKind::VariationWithoutMove => write!(f, "variation does not follow a move"),
+            PgnErrorKind::UnmatchedParenthesis => write!(f, "')' without a matching '('"),
+            PgnErrorKind::UnterminatedVariation => write!(f, "variation is never closed"),
+        }
+    }
+}
+
+// A problem with one game of a PGN file. Games are counted from 1, lines and columns
+// from 1 with columns counted in characters.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PgnError {
+    pub game: usize,
+    pub line: usize,
+    pub column: usize,
+    pub kind: PgnErrorKind,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "game {}, line {}, column {}: {}", self.game, self.line, self.column, self.kind)
+    }
+}
+
+impl Error for PgnError {}
+
+#[derive(Debug, PartialEq, Clone)]
+enum TokenKind {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    StartVariation,
+    EndVariation,
+    San(String),
+    Result(String),
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+// Reads games one at a time from any buffered input, so files of any size can be
+// processed without loading them whole. A game that fails to read is reported as an
+// error and skipped, and reading carries on with the next game.
+pub struct PgnReader<R> {
+    reader: R,
+    // Raw bytes of the line being read, decoded lossily so one badly encoded
+    // game does not end the whole file
+    buffer: Vec<u8>,
+    // The current line and the index of the next character to read in it
+    line: Vec<char>,
+    column: usize,
+    line_number: usize,
+    games: usize,
+    // A tag read while finishing the previous game, which starts the next one
+    peeked: Option<Token>,
+    finished: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            reader,
+            buffer: vec![],
+            line: vec![],
+            column: 0,
+            line_number: 0,
+            games: 0,
+            peeked: None,
+            finished: false,
+        }
+    }
+
+    fn error(&self, line: usize, column: usize, kind: PgnErrorKind) -> PgnError {
+        PgnError { game: self.games, line, column, kind }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, PgnError> {
+        if let Some(token) = self.peeked.take() {
+            return Ok(Some(token));
+        }
+        loop {
+            if !self.next_line_if_done()? {
+                return Ok(None);
+            }
+
+            let (line, column) = (self.line_number, self.column + 1);
+            let token = |kind| Ok(Some(Token { kind, line, column }));
+            let ch = self.line[self.column];
+            self.column += 1;
+            match ch {
+                ch if ch.is_whitespace() || ch == '.' => (),
+                ';' => self.column = self.line.len(),
+                '{' => return token(TokenKind::Comment(self.read_comment(line, column)?)),
+                '[' => {
+                    let (name, value) = self.read_tag().ok_or_else(|| self.error(line, column, PgnErrorKind::InvalidTag))?;
+                    return token(TokenKind::Tag(name, value));
+                }
+                '(' => return token(TokenKind::StartVariation),
+                ')' => return token(TokenKind::EndVariation),
+                '*' => return token(TokenKind::Result("*".to_string())),
+                '$' => {
+                    let digits = self.take_while(|ch| ch.is_ascii_digit());
+                    let nag = digits.parse().map_err(|_| self.error(line, column, PgnErrorKind::InvalidNag(format!("${}", digits))))?;
+                    return token(TokenKind::Nag(nag));
+                }
+                '!' | '?' => {
+                    let suffix = format!("{}{}", ch, self.take_while(|ch| ch == '!' || ch == '?'));
+                    let nag = match suffix.as_str() {
+                        "!" => 1,
+                        "?" => 2,
+                        "!!" => 3,
+                        "??" => 4,
+                        "!?" => 5,
+                        "?!" => 6,
+                        _ => return Err(self.error(line, column, PgnErrorKind::InvalidNag(suffix))),
+                    };
+                    return token(TokenKind::Nag(nag));
+                }
+                ch if ch.is_ascii_alphanumeric() => {
+                    let symbol = format!("{}{}", ch, self.take_while(|ch| ch.is_ascii_alphanumeric() || "_+#=:/-".contains(ch)));
+                    // Move numbers, with any dots after them, carry no information
+                    if symbol.chars().all(|ch| ch.is_ascii_digit()) {
+                        continue;
+                    }
+                    return match symbol.as_str() {
+                        "1-0" | "0-1" | "1/2-1/2" => token(TokenKind::Result(symbol)),
+                        _ => token(TokenKind::San(symbol)),
+                    };
+                }
+                ch => return Err(self.error(line, column, PgnErrorKind::UnexpectedCharacter(ch))),
+            }
+        }
+    }
+
+    // Moves on to the next line when the current one has been read. Lines starting
+    // with '%' are escaped and skipped. False at the end of the input.
+    fn next_line_if_done(&mut self) -> Result<bool, PgnError> {
+        while self.column >= self.line.len() {
+            self.buffer.clear();
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => return Ok(false),
+                Err(error) => {
+                    self.finished = true;
+                    return Err(self.error(self.line_number + 1, 1, PgnErrorKind::Io(error.to_string())));
+                }
+                Ok(_) => {
+                    self.line_number += 1;
+                    let line = self.buffer.strip_suffix(b"\n").unwrap_or(&self.buffer);
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    self.line = String::from_utf8_lossy(line).chars().collect();
+                    self.column = if self.line.first() == Some(&'%') { self.line.len() } else { 0 };
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.column;
+        while self.column < self.line.len() && accept(self.line[self.column]) {
+            self.column += 1;
+        }
+        self.line[start..self.column].iter().collect()
+    }
+
+    // The text up to the closing brace, which may be lines further on. Line breaks
+    // inside the comment become spaces.
+    fn read_comment(&mut self, line: usize, column: usize) -> Result<String, PgnError> {
+        let mut text = String::new();
+        loop {
+            text.push_str(&self.take_while(|ch| ch != '}'));
+            if self.column < self.line.len() {
+                self.column += 1;
+                return Ok(text.trim().to_string());
+            }
+            self.column = self.line.len();
+            if !self.next_line_if_done()? {
+                return Err(self.error(line, column, PgnErrorKind::UnterminatedComment));
+            }
+            text.push(' ');
+        }
+    }
+
+    // The name and value of a tag pair after its opening '[', which has to close on the same line.
+    // A malformed tag skips the rest of its line.
+    fn read_tag(&mut self) -> Option<(String, String)> {
+        let tag = self.read_tag_pair();
+        if tag.is_none() {
+            self.column = self.line.len();
+        }
+        tag
+    }
+
+    fn read_tag_pair(&mut self) -> Option<(String, String)> {
+        self.take_while(char::is_whitespace);
+        let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        self.take_while(char::is_whitespace);
+        if name.is_empty() || self.line.get(self.column) != Some(&'"') {
+            return None;
+        }
+        self.column += 1;
+
+        let mut value = String::new();
+        loop {
+            let ch = *self.line.get(self.column)?;
+            self.column += 1;
+            match ch {
+                '"' => break,
+                '\\' => {
+                    value.push(*self.line.get(self.column)?);
+                    self.column += 1;
+                }
+                ch => value.push(ch),
+            }
+        }
+
+        self.take_while(char::is_whitespace);
+        if self.line.get(self.column) != Some(&']') {
+            return None;
+        }
+        self.column += 1;
+        Some((name, value))
+    }
+
+    // Reads tokens up to the end of the next game, which is its result, the tags of the
+    // game after it or the end of the input
+    fn read_game(&mut self) -> Option<Result<PgnGame, PgnError>> {
+        if self.finished {
+            return None;
+        }
+        self.games += 1;
+
+        let mut tags = vec![];
+        let mut fen_at = (0, 0);
+        let mut builder: Option<GameBuilder> = None;
+        let mut result = None;
+        let mut error = None;
+        let mut read_anything = false;
+        // Set by the first token that is not a tag. A tag after that starts the next game,
+        // even when this one never got far enough to play a move.
+        let mut in_movetext = false;
+        let (mut line, mut column) = (self.line_number, self.column + 1);
+        loop {
+            let token = match self.next_token() {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(err) => {
+                    read_anything = true;
+                    in_movetext |= err.kind != PgnErrorKind::InvalidTag;
+                    error.get_or_insert(err);
+                    if self.finished {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            read_anything = true;
+            (line, column) = (token.line, token.column);
+
+            match token.kind {
+                TokenKind::Tag(name, value) => {
+                    if in_movetext {
+                        self.peeked = Some(Token { kind: TokenKind::Tag(name, value), line, column });
+                        break;
+                    }
+                    if name == "FEN" {
+                        fen_at = (line, column);
+                    }
+                    tags.push((name, value));
+                }
+                TokenKind::Result(game_result) => {
+                    result = Some(game_result);
+                    break;
+                }
+                // Once something is wrong the rest of the game is only read to find its end
+                _ if error.is_some() => in_movetext = true,
+                kind => {
+                    in_movetext = true;
+                    if builder.is_none() {
+                        match GameBuilder::new(&tags) {
+                            Ok(started) => builder = Some(started),
+                            Err(err) => {
+                                error = Some(self.error(fen_at.0, fen_at.1, PgnErrorKind::InvalidFen(err)));
+                                continue;
+                            }
+                        }
+                    }
+                    if let Some(builder) = builder.as_mut()
+                        && let Err(kind) = builder.apply(kind) {
+                        error = Some(self.error(line, column, kind));
+                    }
+                }
+            }
+        }
+
+        if !read_anything {
+            return None;
+        }
+        if let Some(error) = error {
+            return Some(Err(error));
+        }
+        let builder = match builder {
+            Some(builder) => builder,
+            None => match GameBuilder::new(&tags) {
+                Ok(builder) => builder,
+                Err(err) => return Some(Err(self.error(fen_at.0, fen_at.1, PgnErrorKind::InvalidFen(err)))),
+            },
+        };
+        Some(builder.finish(tags, result).map_err(|kind| self.error(line, column, kind)))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_game()
+    }
+}
+
+// A line of moves being read, the mainline or a variation
+struct Line {
+    moves: Vec<PgnMove>,
+    // The position after the last move and the one before it, where variations start
+    position: Position,
+    before: Position,
+    // Comments read before the line's first move
+    comments: Vec<String>,
+}
+
+impl Line {
+    fn new(position: Position) -> Line {
+        Line { moves: vec![], position, before: position, comments: vec![] }
+    }
+}
+
+// Replays movetext as it is read, keeping the variations that are still open on a stack
+struct GameBuilder {
+    start: Position,
+    game: Game,
+    lines: Vec<Line>,
+}
+
+impl GameBuilder {
+    fn new(tags: &[(String, String)]) -> Result<GameBuilder, FenError> {
+        let fen = tags.iter().find(|(name, _)| name == "FEN").map_or(START_FEN, |(_, fen)| fen.as_str());
+        let start = Position::read_fen(fen)?;
+        Ok(GameBuilder { start, game: Game::new(start), lines: vec![Line::new(start)] })
+    }
+
+    fn apply(&mut self, kind: TokenKind) -> Result<(), PgnErrorKind> {
+        let depth = self.lines.len();
+        let line = self.lines.last_mut().unwrap();
+        match kind {
+            TokenKind::San(san) => {
+                let mv = line.position.parse_san(&san).map_err(PgnErrorKind::InvalidMove)?;
+                line.before = line.position;
+                line.position.make_move(mv);
+                let starting_comments = if line.moves.is_empty() && depth > 1 {
+                    std::mem::take(&mut line.comments)
+                } else {
+                    vec![]
+                };
+                line.moves.push(PgnMove { starting_comments, ..PgnMove::new(mv) });
+                if depth == 1 {
+                    self.game.make_move(mv);
+                }
+            }
+            TokenKind::Comment(comment) => match line.moves.last_mut() {
+                Some(last) => {
+                    let text = take_commands(&comment, last);
+                    if !text.is_empty() {
+                        last.comments.push(text);
+                    }
+                }
+                None => line.comments.push(comment),
+            },
+            // Glyphs with no move to annotate are dropped
+            TokenKind::Nag(nag) => {
+                if let Some(last) = line.moves.last_mut() {
+                    last.nags.push(nag);
+                }
+            }
+            TokenKind::StartVariation => {
+                if line.moves.is_empty() {
+                    return Err(PgnErrorKind::VariationWithoutMove);
+                }
+                let before = line.before;
+                self.lines.push(Line::new(before));
+            }
+            TokenKind::EndVariation => {
+                if depth == 1 {
+                    return Err(PgnErrorKind::UnmatchedParenthesis);
+                }
+                let variation = self.lines.pop().unwrap();
+                if !variation.moves.is_empty() {
+                    let parent = self.lines.last_mut().unwrap();
+                    parent.moves.last_mut().unwrap().variations.push(variation.moves);
+                }
+            }
+            TokenKind::Tag(..) | TokenKind::Result(_) => unreachable!("tags and results end the movetext"),
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, tags: Vec<(String, String)>, result: Option<String>) -> Result<PgnGame, PgnErrorKind> {
+        if self.lines.len() > 1 {
+            return Err(PgnErrorKind::UnterminatedVariation);
+        }
+        let mainline = self.lines.pop().unwrap();
+        Ok(PgnGame {
+            tags,
+            start: self.start,
+            game: self.game,
+            moves: mainline.moves,
+            comments: mainline.comments,
+            result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(pgn: &str) -> Vec<Result<PgnGame, PgnError>> {
+        PgnReader::new(pgn.as_bytes()).collect()
+    }
+
+    fn uci(moves: &[PgnMove]) -> Vec<String> {
+        moves.iter().map(|played| played.mv.to_string()).collect()
+    }
+
+    const TWO_GAMES: &str = r#"[Event "Casual"]
+[Site "?"]
+[Date "2024.01.01"]
+[Round "-"]
+[White "Anderssen, \"Adolf\""]
+[Black "Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {Develops} Nc6 3. Bb5 a6!? 4. Ba4 $6 ; line comment 1-0
+Nf6 5. O-O 1-0
+
+[Event "Second"]
+[Result "*"]
+
+1. d4 d5 *
+"#;
+
+    #[test]
+    fn reads_tags_and_mainline() {
+        let games = read(TWO_GAMES);
+        assert_eq!(games.len(), 2);
+
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.tags.len(), 7);
+        assert_eq!(first.tag("White"), Some("Anderssen, \"Adolf\""));
+        assert_eq!(first.tag("Result"), Some("1-0"));
+        assert_eq!(first.tag("Opening"), None);
+        assert_eq!(first.result.as_deref(), Some("1-0"));
+        assert_eq!(uci(&first.moves), ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4", "g8f6", "e1g1"]);
+        assert_eq!(first.game.moves().collect::<Vec<_>>(), first.moves.iter().map(|played| played.mv).collect::<Vec<_>>());
+        assert_eq!(first.moves[2].comments, ["Develops"]);
+        assert_eq!(first.moves[5].nags, [5]);
+        assert_eq!(first.moves[6].nags, [6]);
+        assert_eq!(first.game.to_fen(), "r1bqkb1r/1ppp1ppp/p1n2n2/4p3/B3P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 3 5");
+
+        let second = games[1].as_ref().unwrap();
+        assert_eq!(second.tag("Event"), Some("Second"));
+        assert_eq!(second.result.as_deref(), Some("*"));
+        assert_eq!(uci(&second.moves), ["d2d4", "d7d5"]);
+    }
+
+    #[test]
+    fn reads_nested_variations() {
+        let pgn = "{Start} 1. e4 (1. d4 {Queen's pawn} d5 (1... Nf6 2. c4) 2. c4) (1. c4) 1... e5 (1... c5 $1) 2. Nf3 *";
+        let game = read(pgn).pop().unwrap().unwrap();
+        assert_eq!(game.comments, ["Start"]);
+        assert_eq!(uci(&game.moves), ["e2e4", "e7e5", "g1f3"]);
+
+        let alternatives = &game.moves[0].variations;
+        assert_eq!(alternatives.len(), 2);
+        assert_eq!(uci(&alternatives[0]), ["d2d4", "d7d5", "c2c4"]);
+        assert_eq!(alternatives[0][0].comments, ["Queen's pawn"]);
+        assert_eq!(uci(&alternatives[0][1].variations[0]), ["g8f6", "c2c4"]);
+        assert_eq!(uci(&alternatives[1]), ["c2c4"]);
+        assert_eq!(uci(&game.moves[1].variations[0]), ["c7c5"]);
+        assert_eq!(game.moves[1].variations[0][0].nags, [1]);
+        assert_eq!(game.game.moves().count(), 3);
+    }
+
+    #[test]
+    fn starts_from_fen_tag() {
+        let pgn = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 41. e4 {with [%clk 0:01:00]} 1/2-1/2\n";
+        let game = read(pgn).pop().unwrap().unwrap();
+        assert_eq!(game.start.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 40");
+        assert_eq!(uci(&game.moves), ["e8d7", "e2e4"]);
+        assert_eq!(game.moves[1].comments, ["with"]);
+        assert_eq!(game.moves[1].clock, Some(Duration::from_secs(60)));
+        assert_eq!(game.result.as_deref(), Some("1/2-1/2"));
+    }
+
+    #[test]
+    fn reads_clock_and_eval_commands() {
+        let pgn = "1. e4 { [%eval 0.17] [%clk 1:30:00] } e5 {[%clk 0:00:09.5] [%csl Ge5] forced} 2. Qh5 {[%eval #-3,20]} *";
+        let game = read(pgn).pop().unwrap().unwrap();
+        assert_eq!(game.moves[0].eval, Some(Eval::Centipawns(17)));
+        assert_eq!(game.moves[0].clock, Some(Duration::from_secs(5400)));
+        assert!(game.moves[0].comments.is_empty());
+        assert_eq!(game.moves[1].clock, Some(Duration::from_millis(9500)));
+        assert_eq!(game.moves[1].comments, ["[%csl Ge5] forced"]);
+        assert_eq!(game.moves[2].eval, Some(Eval::Mate(-3)));
+
+        assert_eq!(Eval::Centipawns(-50).to_string(), "-0.50");
+        assert_eq!(Eval::Centipawns(1234).to_string(), "12.34");
+        assert_eq!(format_clock(Duration::from_millis(9500)), "0:00:09.5");
+        assert_eq!(format_clock(Duration::from_secs(5400)), "1:30:00");
+    }
+
+    #[test]
+    fn skips_escapes_and_multiline_comments() {
+        let pgn = "% exported by some tool\n1. e4 {a comment\nover two lines} e5\n%1. d4\n2. Nf3";
+        let game = read(pgn).pop().unwrap().unwrap();
+        assert_eq!(game.moves[0].comments, ["a comment over two lines"]);
+        assert_eq!(uci(&game.moves), ["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(game.result, None);
+    }
+
+    #[test]
+    fn reports_bad_moves_and_keeps_going() {
+        let pgn = "[Event \"One\"]\n\n1. e4 e5 2. Ke3 Nc6 (2... Nf6) 1-0\n\n[Event \"Two\"]\n\n1. d4 (1. Nf3 Nf6 2. Qxf7) 1. d5 *\n\n[Event \"Three\"]\n\n1. c4 *\n";
+        let games = read(pgn);
+        assert_eq!(games.len(), 3);
+
+        let error = games[0].as_ref().unwrap_err();
+        assert_eq!((error.game, error.line, error.column), (1, 3, 13));
+        assert_eq!(error.kind, PgnErrorKind::InvalidMove(SanError::IllegalMove { san: "Ke3".to_string() }));
+        assert_eq!(error.to_string(), "game 1, line 3, column 13: 'Ke3' is not a legal move");
+
+        // Moves inside variations are checked from the position the variation starts in
+        let error = games[1].as_ref().unwrap_err();
+        assert_eq!((error.game, error.line, error.column), (2, 7, 22));
+
+        assert_eq!(uci(&games[2].as_ref().unwrap().moves), ["c2c4"]);
+    }
+
+    #[test]
+    fn reads_past_badly_encoded_text() {
+        let mut pgn = b"[White \"S\xE6misch\"]\r\n\r\n1. d4 {Gr\xFCnfeld} Nf6 *\r\n\r\n".to_vec();
+        pgn.extend_from_slice(b"[White \"Second\"]\n\n1. e4 *\n");
+        let games: Vec<_> = PgnReader::new(pgn.as_slice()).collect();
+        assert_eq!(games.len(), 2);
+
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.tag("White"), Some("S\u{FFFD}misch"));
+        assert_eq!(first.moves[0].comments, ["Gr\u{FFFD}nfeld"]);
+        assert_eq!(uci(&first.moves), ["d2d4", "g8f6"]);
+        assert_eq!(games[1].as_ref().unwrap().tag("White"), Some("Second"));
+    }
+
+    #[test]
+    fn tag_after_movetext_starts_the_next_game() {
+        let games = read("[FEN \"bad\"]\n\n1. e4 e5\n\n[Event \"B\"]\n\n1. d4 d5 *\n");
+        assert_eq!(games.len(), 2);
+        let error = games[0].as_ref().unwrap_err();
+        assert_eq!((error.game, error.line, error.column), (1, 1, 1));
+        assert!(matches!(error.kind, PgnErrorKind::InvalidFen(_)));
+
+        let second = games[1].as_ref().unwrap();
+        assert_eq!(second.tag("Event"), Some("B"));
+        assert_eq!(uci(&second.moves), ["d2d4", "d7d5"]);
+    }
+
+    #[test]
+    fn reports_malformed_movetext() {
+        let error = |pgn: &str| read(pgn).remove(0).unwrap_err();
+        assert_eq!(error("1. e4 e5)").kind, PgnErrorKind::UnmatchedParenthesis);
+        assert_eq!(error("(1. e4) *").kind, PgnErrorKind::VariationWithoutMove);
+        assert_eq!(error("1. e4 (1. d4 *").kind, PgnErrorKind::UnterminatedVariation);
+        assert_eq!(error("1. e4 {never closed\n2. Nf3").kind, PgnErrorKind::UnterminatedComment);
+        assert_eq!(error("1. e4 !!! *").kind, PgnErrorKind::InvalidNag("!!!".to_string()));
+        assert_eq!(error("1. e4 & *").kind, PgnErrorKind::UnexpectedCharacter('&'));
+        assert_eq!(error("[Event Casual]\n1. e4 *").kind, PgnErrorKind::InvalidTag);
+        assert!(matches!(error("[FEN \"8/8/8 w - - 0 1\"]\n1. e4 *"), PgnError { line: 1, column: 1, kind: PgnErrorKind::InvalidFen(_), .. }));
+    }
+
+    #[test]
+    fn empty_input_has_no_games() {
+        assert!(read("").is_empty());
+        assert!(read("\n\n% nothing here\n").is_empty());
+    }
+}
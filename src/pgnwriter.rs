@@ -0,0 +1,262 @@
+use std::io::{self, Write};
+
+use crate::fen::START_FEN;
+use crate::game::*;
+use crate::pgn::{format_clock, PgnGame, PgnMove};
+use crate::position::Position;
+
+// The tags every exported game starts with, in this order, and their values when unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+const LINE_WIDTH: usize = 80;
+
+// Writes games in PGN export format: the seven tag roster first, SetUp and FEN for games
+// that do not start from the standard position, and movetext wrapped to 80 columns
+pub struct PgnWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> PgnWriter<W> {
+    pub fn new(writer: W) -> PgnWriter<W> {
+        PgnWriter { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    // Writes one game followed by a blank line. Every move has to be legal where it is played.
+    pub fn write_game(&mut self, game: &PgnGame) -> io::Result<()> {
+        let result = game.result.as_deref().or(game.tag("Result")).unwrap_or("*");
+
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = if name == "Result" { result } else { game.tag(name).unwrap_or(default) };
+            write_tag(&mut self.writer, name, value)?;
+        }
+        let fen = game.start.to_fen();
+        if fen != START_FEN {
+            write_tag(&mut self.writer, "SetUp", "1")?;
+            write_tag(&mut self.writer, "FEN", &fen)?;
+        }
+        for (name, value) in &game.tags {
+            let written = SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name) || name == "SetUp" || name == "FEN";
+            if !written {
+                write_tag(&mut self.writer, name, value)?;
+            }
+        }
+        writeln!(self.writer)?;
+
+        let mut tokens = vec![];
+        push_comments(&mut tokens, &game.comments);
+        push_line(&mut tokens, game.start, &game.moves);
+        tokens.push(result.to_string());
+        for line in wrap(&tokens) {
+            writeln!(self.writer, "{}", line)?;
+        }
+        writeln!(self.writer)
+    }
+}
+
+impl PgnGame {
+    // The game in PGN export format
+    pub fn to_pgn(&self) -> String {
+        let mut writer = PgnWriter::new(vec![]);
+        writer.write_game(self).expect("writing to a Vec cannot fail");
+        String::from_utf8(writer.into_inner()).expect("PGN output is UTF-8")
+    }
+}
+
+fn write_tag(writer: &mut impl Write, name: &str, value: &str) -> io::Result<()> {
+    writeln!(writer, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Comments go out one word per token so long ones wrap like the rest of the movetext.
+// PGN has no way to escape a closing brace inside a comment, so any are dropped.
+fn push_comments(tokens: &mut Vec<String>, comments: &[String]) {
+    for comment in comments {
+        let comment = comment.replace('}', "");
+        let words: Vec<&str> = comment.split_whitespace().collect();
+        match words.as_slice() {
+            [] => tokens.push("{}".to_string()),
+            [word] => tokens.push(format!("{{{}}}", word)),
+            [first, middle @ .., last] => {
+                tokens.push(format!("{{{}", first));
+                tokens.extend(middle.iter().map(|word| word.to_string()));
+                tokens.push(format!("{}}}", last));
+            }
+        }
+    }
+}
+
+// The movetext of a line played from `position`. Black moves get a number like 12... when
+// they start the line or follow a comment or variation, since the reader loses track there.
+fn push_line(tokens: &mut Vec<String>, mut position: Position, moves: &[PgnMove]) {
+    let mut interrupted = true;
+    for played in moves {
+        if !played.starting_comments.is_empty() {
+            push_comments(tokens, &played.starting_comments);
+        }
+        // Numbers share a token with their move so wrapping never separates them
+        let san = position.to_san(played.mv);
+        tokens.push(match position.active_color {
+            Color::White => format!("{}. {}", position.fullmoves, san),
+            Color::Black if interrupted => format!("{}... {}", position.fullmoves, san),
+            Color::Black => san,
+        });
+        tokens.extend(played.nags.iter().map(|nag| format!("${}", nag)));
+
+        // Clock and eval commands go at the front of the first comment
+        let mut commands = vec![];
+        if let Some(eval) = played.eval {
+            commands.push(format!("[%eval {}]", eval));
+        }
+        if let Some(clock) = played.clock {
+            commands.push(format!("[%clk {}]", format_clock(clock)));
+        }
+        let mut comments = played.comments.clone();
+        if !commands.is_empty() {
+            let commands = commands.join(" ");
+            match comments.first_mut() {
+                Some(first) => *first = format!("{} {}", commands, first),
+                None => comments.push(commands),
+            }
+        }
+        push_comments(tokens, &comments);
+        interrupted = !comments.is_empty();
+
+        for variation in &played.variations {
+            let start = tokens.len();
+            push_line(tokens, position, variation);
+            if tokens.len() > start {
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+                interrupted = true;
+            }
+        }
+
+        position.make_move(played.mv);
+    }
+}
+
+// Joins tokens with spaces into lines of at most LINE_WIDTH characters. A token longer
+// than that gets a line of its own.
+fn wrap(tokens: &[String]) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.chars().count() + 1 + token.chars().count() > LINE_WIDTH {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(token);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::pgn::{Eval, PgnReader};
+
+    fn read(pgn: &str) -> PgnGame {
+        PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn writes_tags_and_numbered_movetext() {
+        let game = read("[White \"Morphy\"]\n[Annotator \"me\"]\n[Result \"1-0\"]\n\n\
+            {Opening} 1. e4 e5 (1... c5 2. Nf3) 2. Nf3 {Main} Nc6 $1 (2... d6 {Philidor}) 3. Bb5 1-0");
+        assert_eq!(game.to_pgn(), "\
+[Event \"?\"]
+[Site \"?\"]
+[Date \"????.??.??\"]
+[Round \"?\"]
+[White \"Morphy\"]
+[Black \"?\"]
+[Result \"1-0\"]
+[Annotator \"me\"]
+
+{Opening} 1. e4 e5 (1... c5 2. Nf3) 2. Nf3 {Main} 2... Nc6 $1 (2... d6
+{Philidor}) 3. Bb5 1-0
+
+");
+    }
+
+    #[test]
+    fn writes_fen_for_other_starts() {
+        let mut game = read("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n[SetUp \"1\"]\n\n40... Kd7 41. e4 (41. e3 Kd6) *");
+        game.tags.push(("Event".to_string(), "Quote \"and\" \\slash".to_string()));
+        let pgn = game.to_pgn();
+        assert!(pgn.starts_with("[Event \"Quote \\\"and\\\" \\\\slash\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n"));
+        assert_eq!(pgn.split("\n\n").nth(1), Some("40... Kd7 41. e4 (41. e3 Kd6) *"));
+        assert_eq!(read(&pgn).tag("Event"), Some("Quote \"and\" \\slash"));
+    }
+
+    #[test]
+    fn writes_clock_and_eval_commands() {
+        let mut game = read("1. d4 {Solid} d5 *");
+        game.moves[0].eval = Some(Eval::Centipawns(17));
+        game.moves[0].clock = Some(Duration::from_secs(5399));
+        game.moves[1].clock = Some(Duration::from_millis(5300));
+        assert!(game.to_pgn().ends_with("\n1. d4 {[%eval 0.17] [%clk 1:29:59] Solid} 1... d5 {[%clk 0:00:05.3]} *\n\n"));
+
+        let again = read(&game.to_pgn());
+        assert_eq!(again.moves, game.moves);
+    }
+
+    #[test]
+    fn wraps_at_eighty_columns() {
+        let mut movetext = String::new();
+        for _ in 0..10 {
+            movetext.push_str("Nf3 Nf6 Ng1 Ng8 ");
+        }
+        let mut game = read(&format!("{} {{{}}} *", movetext, "a long comment ".repeat(12)));
+        game.moves[4].variations.push(read("1. e4 {Another move that needs a comment long enough to wrap on its own} e5 *").moves);
+        let pgn = game.to_pgn();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert!(movetext.lines().count() > 3);
+        assert!(movetext.lines().all(|line| line.len() <= 80), "{}", movetext);
+
+        let again = read(&pgn);
+        assert_eq!(again.moves, game.moves);
+        assert_eq!(again.game.position, game.game.position);
+    }
+
+    #[test]
+    fn drops_closing_braces_from_comments() {
+        let mut game = read("1. e4 e5 *");
+        game.comments.push("set {x} aside".to_string());
+        game.moves[0].comments.push("}".to_string());
+        game.moves[1].comments.push("ends} early".to_string());
+        assert!(game.to_pgn().ends_with("\n{set {x aside} 1. e4 {} 1... e5 {ends early} *\n\n"));
+
+        let again = read(&game.to_pgn());
+        assert_eq!(again.comments, ["set {x aside"]);
+        assert_eq!(again.moves[1].comments, ["ends early"]);
+        assert_eq!(again.game.position, game.game.position);
+    }
+
+    #[test]
+    fn round_trips_nested_variations() {
+        let pgn = "1. e4 (1. d4 d5 (1... Nf6 2. c4 (2. Nf3) 2... e6) 2. c4) 1... e5 2. Nf3 $14\n(2. f4 exf4) 2... Nc6 *";
+        let game = read(pgn);
+        assert_eq!(game.to_pgn().split("\n\n").nth(1), Some(pgn));
+        assert_eq!(read(&game.to_pgn()).moves, game.moves);
+    }
+}
@@ -0,0 +1,207 @@
+use std::hash::{Hash, Hasher};
+
+use crate::attacks::pawn_attacks;
+use crate::board::Board;
+use crate::game::*;
+use crate::square::Square;
+
+// Everything that makes up a chess position, without any history. Small and
+// Copy, so positions can be stored, compared and hashed without allocating.
+// The fields the Zobrist key covers are only changed through the setters below,
+// so outside the crate the key can never go stale.
+#[derive(Debug, Copy, Clone)]
+pub struct Position {
+    pub(crate) board: Board,
+    pub(crate) active_color: Color,
+    pub(crate) castling_rights: CastlingRights,
+    pub(crate) en_passant: Option<Square>,
+    pub(crate) ply: u16,
+    pub(crate) fullmoves: u16,
+    // Zobrist key of everything above but the move counters, see zobrist.rs
+    pub(crate) key: u64,
+}
+
+// Positions are equal when the same moves are legal in them, so the move counters,
+// the key derived from the rest and an en passant square nobody can capture on are
+// left out of equality and hashing
+impl PartialEq for Position {
+    fn eq(&self, other: &Position) -> bool {
+        self.board == other.board
+            && self.active_color == other.active_color
+            && self.castling_rights == other.castling_rights
+            && self.hashed_en_passant() == other.hashed_en_passant()
+    }
+}
+
+impl Eq for Position {}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.hash(state);
+        self.active_color.hash(state);
+        self.castling_rights.hash(state);
+        self.hashed_en_passant().hash(state);
+    }
+}
+
+impl Position {
+    // A position with no pieces, white to move and no castling rights
+    pub fn empty() -> Position {
+        Position {
+            board: Board::empty(),
+            active_color: Color::White,
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            ply: 0,
+            fullmoves: 1,
+            // Nothing on the board and white to move hashes to 0
+            key: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn active_color(&self) -> Color {
+        self.active_color
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    // Plies since the last capture or pawn move, for the fifty move rule
+    pub fn ply(&self) -> u16 {
+        self.ply
+    }
+
+    pub fn fullmoves(&self) -> u16 {
+        self.fullmoves
+    }
+
+    // Places a piece on the square with the given index, returning the piece it replaced
+    pub fn put_piece(&mut self, index: usize, color: Color, piece_type: PieceType) -> Option<(Color, PieceType)> {
+        let replaced = self.board.remove_piece(index);
+        self.board.put_piece(index, color, piece_type);
+        self.refresh_zobrist_key();
+        replaced
+    }
+
+    // Clears the square with the given index, returning the piece that stood there
+    pub fn remove_piece(&mut self, index: usize) -> Option<(Color, PieceType)> {
+        let removed = self.board.remove_piece(index);
+        self.refresh_zobrist_key();
+        removed
+    }
+
+    pub fn set_active_color(&mut self, color: Color) {
+        self.active_color = color;
+        self.refresh_zobrist_key();
+    }
+
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.castling_rights = rights;
+        self.refresh_zobrist_key();
+    }
+
+    pub fn set_en_passant(&mut self, en_passant: Option<Square>) {
+        self.en_passant = en_passant;
+        self.refresh_zobrist_key();
+    }
+
+    // The move counters are not part of the key
+    pub fn set_ply(&mut self, ply: u16) {
+        self.ply = ply;
+    }
+
+    pub fn set_fullmoves(&mut self, fullmoves: u16) {
+        self.fullmoves = fullmoves;
+    }
+
+    // The en passant square when a pawn of the side to move attacks it, which is all the
+    // key and equality look at: a double push nobody can take on changes nothing.
+    pub(crate) fn hashed_en_passant(&self) -> Option<Square> {
+        self.en_passant.filter(|square| {
+            !(pawn_attacks(square.index(), self.active_color.opposite()) & self.piece_bitboard(self.active_color, PieceType::Pawn)).is_empty()
+        })
+    }
+
+    // The color and type of the piece standing on the square with the given index, if any
+    pub fn piece_at(&self, index: usize) -> Option<(Color, PieceType)> {
+        self.board.piece_at(index)
+    }
+
+    // Bitboard of every square occupied by a piece of `color`
+    pub fn color_occupancy(&self, color: Color) -> PiecePosition {
+        self.board.color_occupancy(color)
+    }
+
+    // Bitboard of every square holding a piece of the given color and type
+    pub fn piece_bitboard(&self, color: Color, piece_type: PieceType) -> PiecePosition {
+        self.board.pieces(color, piece_type)
+    }
+
+    // Bitboard of every occupied square
+    pub fn occupancy(&self) -> PiecePosition {
+        self.board.occupancy()
+    }
+
+    pub fn board_rep(&self) -> String {
+        let mut board = "".to_owned();
+        let mut temp = "".to_owned();
+
+        for i in 0..64 {
+            match self.piece_at(i) {
+                None => temp.push_str(&index_to_position(i)),
+                Some((color, piece_type)) => {
+                    temp.push(piece_char(color, piece_type));
+                    temp.push(' ');
+                }
+            }
+            if (i + 1) % 8 == 0 {
+                temp.push('\n');
+                board.insert_str(0, &temp );
+                temp.clear();
+            }
+        }
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::mem::size_of;
+
+    #[test]
+    fn positions_are_small_and_hashable() {
+        // Everything besides the board fits in two words: the key, the counters and a byte each
+        // for the side to move, castling rights and en passant square
+        assert!(size_of::<Position>() <= size_of::<Board>() + 16, "Position is {} bytes", size_of::<Position>());
+
+        let start = Position::read_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let mut seen = HashSet::new();
+        seen.insert(start);
+        for mv in start.legal_moves() {
+            let mut next = start;
+            next.make_move(mv);
+            assert!(seen.insert(next));
+        }
+        assert!(!seen.insert(start));
+        assert_eq!(seen.len(), 21);
+
+        // The move counters do not make a different position
+        let mut later = start;
+        later.ply = 12;
+        later.fullmoves = 30;
+        assert_eq!(later, start);
+        assert!(!seen.insert(later));
+    }
+}
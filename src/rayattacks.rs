@@ -1,171 +1,74 @@
-use std::{i64, u64};
-
+use crate::square::Square;
+use crate::bitboard::Bitboard;
+use crate::attacks::{Direction, RAYS};
 use crate::utils::*;
 
-type Bitboard = u64;
-
-
-static COL_MAP : [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
-
 // Converts the index of the 64 to a human readable chess notation
 // Will mainly be used for printing and debugging
 pub fn index_to_coordinate(index: usize) -> (usize, usize) {
-    let column = index % 8 + 1;
-    let row = index / 8 + 1;
-
-    return (column, row);
+    let square = Square::ALL[index];
+    (square.file().index() + 1, square.rank().index() + 1)
 }
 
+// The ray tables of crate::attacks, one field per direction
 pub struct Rays {
-    n_rays: Vec<u64>,
-    e_rays: Vec<u64>,
-    w_rays: Vec<u64>,
-    s_rays: Vec<u64>,
-    nw_rays: Vec<u64>,
-    ne_rays: Vec<u64>,
-    sw_rays: Vec<u64>,
-    se_rays: Vec<u64>,
+    n_rays: &'static [Bitboard; 64],
+    e_rays: &'static [Bitboard; 64],
+    w_rays: &'static [Bitboard; 64],
+    s_rays: &'static [Bitboard; 64],
+    nw_rays: &'static [Bitboard; 64],
+    ne_rays: &'static [Bitboard; 64],
+    sw_rays: &'static [Bitboard; 64],
+    se_rays: &'static [Bitboard; 64],
 }
 
 impl Rays {
-    fn init() -> Self {
-        let mut n_rays = vec![];
-        let mut e_rays = vec![];
-        let mut w_rays = vec![];
-        let mut s_rays = vec![];
-        let mut nw_rays = vec![];
-        let mut ne_rays = vec![];
-        let mut sw_rays = vec![];
-        let mut se_rays = vec![];
-
-       for i in 0..63 {
-           let coord = index_to_coordinate(i);
-           let col = coord.0;
-           let row = coord.1;
-            n_rays.push(make_ray(row as i64, col as i64, Direction::North));
-            e_rays.push(make_ray(row as i64, col as i64, Direction::East));
-            w_rays.push(make_ray(row as i64, col as i64, Direction::West));
-            s_rays.push(make_ray(row as i64, col as i64, Direction::South));
-
-            nw_rays.push(make_ray(row as i64, col as i64, Direction::Northwest));
-            ne_rays.push(make_ray(row as i64, col as i64, Direction::Northeast));
-            sw_rays.push(make_ray(row as i64, col as i64, Direction::Southwest));
-            se_rays.push(make_ray(row as i64, col as i64, Direction::Southeast));
-       } 
-
-        
-        return Self{
-            n_rays:n_rays, 
-            e_rays:e_rays,
-            w_rays:w_rays,
-            s_rays:s_rays,
-            nw_rays:nw_rays,
-            ne_rays:ne_rays,
-            sw_rays:sw_rays,
-            se_rays:se_rays,
+    pub const fn init() -> Self {
+        Self {
+            n_rays: &RAYS[Direction::North as usize],
+            e_rays: &RAYS[Direction::East as usize],
+            w_rays: &RAYS[Direction::West as usize],
+            s_rays: &RAYS[Direction::South as usize],
+            nw_rays: &RAYS[Direction::Northwest as usize],
+            ne_rays: &RAYS[Direction::Northeast as usize],
+            sw_rays: &RAYS[Direction::Southwest as usize],
+            se_rays: &RAYS[Direction::Southeast as usize],
         }
     }
-}
-
-fn n_ray(row: i64, col: i64) -> Bitboard {
-    let mut bitboard = 0;
-
-    for offset in 1..=8{
-        if row + offset  > 8 {
-            break;
-        }
-        bitboard = set_bit(bitboard, row + offset, col);
-    }
-    return bitboard;
-}
-
-enum Direction {
-   North,
-   South,
-   East,
-   West,
-   Northwest,
-   Northeast,
-   Southwest,
-   Southeast,
-}
-
 
-fn make_ray(row: i64, col: i64, direction: Direction) -> Bitboard {
-    let mut bitboard: Bitboard = 0;
-
-    for offset in 1..=8 {
-        let (r_offset, c_offset) = match direction {
-            Direction::North => (row + offset, col),
-            Direction::South => (row - offset, col),
-            Direction::East => (row, col + offset),
-            Direction::West => (row, col - offset),
-            Direction::Northwest => (row + offset, col - offset),
-            Direction::Northeast => (row + offset, col + offset),
-            Direction::Southwest => (row - offset, col - offset),
-            Direction::Southeast => (row - offset, col + offset),
-        };
-
-        if r_offset < 1 || r_offset > 8 || c_offset < 0 || c_offset > 8{
-            break;
-        }
-
-        //bitboard |= 1 << idx;
-        bitboard = set_bit(bitboard, r_offset, c_offset);
+    // Attacks of a bishop on `square`, each diagonal cut off at the first blocker
+    pub fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        blocked_ray_attacks(self.nw_rays[square], self.nw_rays, true, occupancy)
+            | blocked_ray_attacks(self.ne_rays[square], self.ne_rays, true, occupancy)
+            | blocked_ray_attacks(self.sw_rays[square], self.sw_rays, false, occupancy)
+            | blocked_ray_attacks(self.se_rays[square], self.se_rays, false, occupancy)
     }
-    bitboard
-}
 
-fn set_bit(bitboard: Bitboard, row: i64, col: i64) -> Bitboard{
-    if row < 1 || row > 8 || col < 1 || col > 8 {
-        return bitboard;
+    // Attacks of a rook on `square`, each file/rank cut off at the first blocker
+    pub fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        blocked_ray_attacks(self.n_rays[square], self.n_rays, true, occupancy)
+            | blocked_ray_attacks(self.e_rays[square], self.e_rays, true, occupancy)
+            | blocked_ray_attacks(self.s_rays[square], self.s_rays, false, occupancy)
+            | blocked_ray_attacks(self.w_rays[square], self.w_rays, false, occupancy)
     }
-    return bitboard | (1 << ((col - 1) + (row - 1) * 8))
-}
-
-fn bitboard_to_string (bitboard: Bitboard, mark:Option<usize>) -> String {
-    let mut row = "".to_owned();
-    let mut board  = "".to_owned();
-
-    for i in 0..64 {
-        let value = (bitboard >> i) & 1;
-        let s = if value == 0 {
-            ".".to_owned()
-        } else {
-            value.to_string()
-        };
-
-        match mark {
-            Some(idx) => {
-                if i == idx {
-                    row.push_str("X");
-                }else {
-                    row.push_str(&s);
-                }
-            }
-            None => row.push_str(&s),
-        }
 
-        if (i + 1) % 8 == 0 {
-            row.push_str("\n");
-            board.insert_str(0, &row);
-            row.clear();
-        }
+    pub fn queen_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.bishop_attacks(square, occupancy) | self.rook_attacks(square, occupancy)
     }
-    return board
 }
 
-fn blocked_ray_attacks(ray: Bitboard, ray_family: &Vec<Bitboard>, forward_ray: bool, occupancy: Bitboard) -> Bitboard {
+pub fn blocked_ray_attacks(ray: Bitboard, ray_family: &[Bitboard], forward_ray: bool, occupancy: Bitboard) -> Bitboard {
     let overlap = ray & occupancy; //Where the bitboard from the ray for an attack is in union
-    let mut bit_index;
-    if forward_ray {
-        bit_index = bit_scan(overlap);
-
+    let first_blocker = if forward_ray {
+        bit_scan(overlap.0)
     } else {
-        bit_index = bit_scan_backwards(overlap);
-    }
+        bit_scan_backwards(overlap.0)
+    };
+    let Some(bit_index) = first_blocker else {
+        return ray;
+    };
     let ray_after = ray_family[bit_index];
-    return ray ^ ray_after;
+    ray ^ ray_after
 }
 
 #[cfg(test)]
@@ -180,23 +83,23 @@ mod tests {
 
     #[test]
     fn test_blocked_ray(){
-        let mut occupancy = 0;
+        let mut occupancy = Bitboard::EMPTY;
         for i in 0..16{
             if i == 5{
                 continue;
             }
-            occupancy |= 1 << i;
+            occupancy |= Bitboard(1 << i);
         }
 
-        occupancy |= 1 << 22;
+        occupancy |= Bitboard(1 << 22);
         for i in 48..64 {
             if i == 57 || i == 49 {
                 continue;
             }
-            occupancy |= 1 << i;
+            occupancy |= Bitboard(1 << i);
         }
-        occupancy |= 1 << 41;
-        occupancy |= 1 << 42;
+        occupancy |= Bitboard(1 << 41);
+        occupancy |= Bitboard(1 << 42);
 
         let rays = Rays::init();
         let row = 6;
@@ -207,9 +110,26 @@ mod tests {
 
         println!("Here is the occupancy bitboard:\n{}", bitboard_to_string(occupancy, Some(idx)));
         println!("Here is the ray we're testing: \n{}", bitboard_to_string(rays.sw_rays[idx], Some(idx)));
-        let blocked_ray = blocked_ray_attacks(rays.sw_rays[idx], &rays.sw_rays, false, occupancy);
+        let blocked_ray = blocked_ray_attacks(rays.sw_rays[idx], rays.sw_rays, false, occupancy);
         println!("Here is the ray we're testing: \n{}", bitboard_to_string(blocked_ray, Some(idx)));
     }
+    #[test]
+    fn test_slider_attacks_stop_at_blockers(){
+        let rays = Rays::init();
+        assert_eq!(rays.rook_attacks(0, Bitboard::EMPTY).popcount(), 14);
+        assert_eq!(rays.rook_attacks(63, Bitboard::EMPTY).popcount(), 14);
+        assert_eq!(rays.bishop_attacks(27, Bitboard::EMPTY).popcount(), 13);
+
+        // Rook on d4 with blockers on d6 and b4: the blockers are attacked, nothing behind them
+        let occupancy = Square::D6.bitboard() | Square::B4.bitboard();
+        let attacks = rays.rook_attacks(27, occupancy);
+        assert!(attacks.contains(Square::D6));
+        assert!(!attacks.contains(Square::D7));
+        assert!(attacks.contains(Square::B4));
+        assert!(!attacks.contains(Square::A4));
+        assert_eq!(attacks.popcount(), 11);
+    }
+
     // #[test]
     // fn make_general_ray(){
     //     let row = 4;
@@ -0,0 +1,307 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::game::*;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::square::{File, Rank, Square};
+
+// Why a SAN string could not be resolved to a move
+#[derive(Debug, PartialEq, Clone)]
+pub enum SanError {
+    // Not shaped like a move at all
+    InvalidSyntax { san: String },
+    // Well formed, but no legal move fits it
+    IllegalMove { san: String },
+    // More than one legal move fits it
+    AmbiguousMove { san: String, candidates: Vec<Move> },
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax { san } => write!(f, "'{}' is not a move in SAN", san),
+            SanError::IllegalMove { san } => write!(f, "'{}' is not a legal move", san),
+            SanError::AmbiguousMove { san, candidates } => {
+                let candidates: Vec<String> = candidates.iter().map(|mv| mv.to_string()).collect();
+                write!(f, "'{}' is ambiguous between {}", san, candidates.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for SanError {}
+
+// The parts of a non-castling SAN move. The origin file and rank are only
+// present when the move was disambiguated.
+struct SanParts {
+    piece_type: PieceType,
+    from_file: Option<File>,
+    from_rank: Option<Rank>,
+    to: Square,
+    promotion: Option<PieceType>,
+}
+
+fn piece_from_letter(letter: char) -> Option<PieceType> {
+    match letter {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+// Splits a SAN move, already stripped of check marks and annotations, into its parts
+fn split_san(san: &str) -> Option<SanParts> {
+    let mut chars: Vec<char> = san.chars().collect();
+
+    // e8=Q, also accepted without the '='
+    let mut promotion = None;
+    if let Some(&last) = chars.last()
+        && let Some(piece_type) = piece_from_letter(last) {
+        promotion = Some(piece_type);
+        chars.pop();
+        if chars.last() == Some(&'=') {
+            chars.pop();
+        }
+    }
+
+    if chars.len() < 2 {
+        return None;
+    }
+    let to_rank = Rank::from_char(chars.pop()?)?;
+    let to_file = File::from_char(chars.pop()?)?;
+
+    let mut rest = chars.as_slice();
+    let piece_type = match rest.first().and_then(|&letter| piece_from_letter(letter)) {
+        Some(piece_type) => {
+            rest = &rest[1..];
+            piece_type
+        }
+        None => PieceType::Pawn,
+    };
+    if rest.last() == Some(&'x') {
+        rest = &rest[..rest.len() - 1];
+    }
+
+    let (from_file, from_rank) = match rest {
+        [] => (None, None),
+        [file, rank] => (Some(File::from_char(*file)?), Some(Rank::from_char(*rank)?)),
+        [ch] => match (File::from_char(*ch), Rank::from_char(*ch)) {
+            (Some(file), _) => (Some(file), None),
+            (_, Some(rank)) => (None, Some(rank)),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(SanParts { piece_type, from_file, from_rank, to: Square::new(to_file, to_rank), promotion })
+}
+
+impl Position {
+    // Resolves a move in Standard Algebraic Notation (Nbd7, exd5, O-O-O, e8=Q+) to the
+    // legal move it names. Check and mate marks and !/? annotations are ignored, and
+    // extra disambiguation is accepted.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let trimmed = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.legal_moves();
+
+        let candidates: Vec<Move> = match trimmed {
+            "O-O" | "0-0" | "O-O-O" | "0-0-0" => {
+                let kingside = trimmed.len() == 3;
+                legal_moves.into_iter()
+                    .filter(|mv| mv.is_castling() && (mv.to() > mv.from()) == kingside)
+                    .collect()
+            }
+            _ => {
+                let parts = split_san(trimmed).ok_or_else(|| SanError::InvalidSyntax { san: san.to_string() })?;
+                legal_moves.into_iter().filter(|mv| {
+                    let from = Square::ALL[mv.from()];
+                    !mv.is_castling()
+                        && mv.to() == parts.to.index()
+                        && self.piece_at(mv.from()).map(|(_, piece_type)| piece_type) == Some(parts.piece_type)
+                        && mv.promotion() == parts.promotion
+                        && parts.from_file.is_none_or(|file| from.file() == file)
+                        && parts.from_rank.is_none_or(|rank| from.rank() == rank)
+                }).collect()
+            }
+        };
+
+        match candidates.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(SanError::IllegalMove { san: san.to_string() }),
+            _ => Err(SanError::AmbiguousMove { san: san.to_string(), candidates }),
+        }
+    }
+
+    // Writes a legal move in Standard Algebraic Notation, disambiguated only as far as
+    // needed and followed by + or # when it gives check or mate
+    pub fn to_san(&self, mv: Move) -> String {
+        let mut san = self.san_without_check(mv);
+
+        let mut after = *self;
+        after.make_move(mv);
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    fn san_without_check(&self, mv: Move) -> String {
+        if mv.is_castling() {
+            return if mv.to() > mv.from() { "O-O".to_string() } else { "O-O-O".to_string() };
+        }
+
+        let from = Square::ALL[mv.from()];
+        let to = Square::ALL[mv.to()];
+        let piece_type = match self.piece_at(mv.from()) {
+            Some((_, piece_type)) => piece_type,
+            None => panic!("No piece to move on {}", from),
+        };
+
+        let mut san = String::new();
+        if piece_type == PieceType::Pawn {
+            if mv.is_capture() {
+                san.push(from.file().to_char());
+            }
+        } else {
+            san.push(piece_char(Color::White, piece_type));
+
+            // Other pieces of the same type that could also go to the target square
+            let rivals: Vec<Square> = self.legal_moves().into_iter()
+                .filter(|other| other.to() == mv.to() && other.from() != mv.from())
+                .filter(|other| self.piece_at(other.from()) == self.piece_at(mv.from()))
+                .map(|other| Square::ALL[other.from()])
+                .collect();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|rival| rival.file() != from.file()) {
+                    san.push(from.file().to_char());
+                } else if rivals.iter().all(|rival| rival.rank() != from.rank()) {
+                    san.push(from.rank().to_char());
+                } else {
+                    san.push_str(&from.to_string());
+                }
+            }
+        }
+
+        if mv.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&to.to_string());
+        if let Some(promotion) = mv.promotion() {
+            san.push('=');
+            san.push(piece_char(Color::White, promotion));
+        }
+        san
+    }
+}
+
+// SAN for the current position of a Game
+impl Game {
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        self.position.parse_san(san)
+    }
+
+    pub fn to_san(&self, mv: Move) -> String {
+        self.position.to_san(mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san(fen: &str, uci: &str) -> String {
+        let game = Game::read_fen(fen).unwrap();
+        let mv = game.legal_moves().into_iter().find(|mv| mv.to_string() == uci).unwrap();
+        game.to_san(mv)
+    }
+
+    fn parse(fen: &str, san: &str) -> Result<String, SanError> {
+        Game::read_fen(fen).unwrap().parse_san(san).map(|mv| mv.to_string())
+    }
+
+    #[test]
+    fn parses_common_moves() {
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+        assert_eq!(parse(fen, "Qxf7#"), Ok("h5f7".to_string()));
+        assert_eq!(parse(fen, "Qxf7"), Ok("h5f7".to_string()));
+        assert_eq!(parse(fen, "Nf3"), Ok("g1f3".to_string()));
+        assert_eq!(parse(fen, "a4"), Ok("a2a4".to_string()));
+        assert_eq!(parse(fen, "Bxf7+!?"), Ok("c4f7".to_string()));
+
+        let fen = "r3k2r/pppq1ppp/2npbn2/4p3/3PP3/2N1BN2/PPPQ1PPP/R3KB1R b KQkq d3 0 7";
+        assert_eq!(parse(fen, "exd4"), Ok("e5d4".to_string()));
+        assert_eq!(parse(fen, "O-O-O"), Ok("e8c8".to_string()));
+        assert_eq!(parse(fen, "0-0"), Ok("e8g8".to_string()));
+        assert_eq!(parse(fen, "Nb4"), Ok("c6b4".to_string()));
+
+        let fen = "r1b1kbnr/pppn1ppp/8/3pp3/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1";
+        assert_eq!(parse(fen, "Nbd7"), Err(SanError::IllegalMove { san: "Nbd7".to_string() }));
+        assert_eq!(parse("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "Nbd2"), Ok("b1d2".to_string()));
+        assert_eq!(parse("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "Nb1d2"), Ok("b1d2".to_string()));
+        assert_eq!(parse("4k3/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8=Q+"), Err(SanError::IllegalMove { san: "e8=Q+".to_string() }));
+        assert_eq!(parse("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8=Q+"), Ok("e7e8q".to_string()));
+        assert_eq!(parse("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8N"), Ok("e7e8n".to_string()));
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+        assert_eq!(parse(fen, "Nd2"), Err(SanError::AmbiguousMove {
+            san: "Nd2".to_string(),
+            candidates: vec![Move::new(1, 11, crate::moves::MoveFlags::NONE), Move::new(5, 11, crate::moves::MoveFlags::NONE)],
+        }));
+        assert_eq!(parse(fen, "Nd9"), Err(SanError::InvalidSyntax { san: "Nd9".to_string() }));
+        assert_eq!(parse(fen, ""), Err(SanError::InvalidSyntax { san: "".to_string() }));
+        assert_eq!(parse(fen, "hello"), Err(SanError::InvalidSyntax { san: "hello".to_string() }));
+        assert_eq!(parse(fen, "O-O"), Err(SanError::IllegalMove { san: "O-O".to_string() }));
+    }
+
+    #[test]
+    fn writes_minimal_disambiguation() {
+        let knights = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+        assert_eq!(san(knights, "b1d2"), "Nbd2");
+        assert_eq!(san(knights, "b1c3"), "Nc3");
+
+        let rooks = "4k3/R7/8/8/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(rooks, "a1a4"), "R1a4");
+        assert_eq!(san(rooks, "a7a4"), "R7a4");
+
+        // The queen on a1 shares a file with a3 and a rank with c1
+        let queens = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(san(queens, "a1b2"), "Qa1b2");
+        assert_eq!(san(queens, "a3b2"), "Q3b2");
+        assert_eq!(san(queens, "c1b2"), "Qcb2");
+    }
+
+    #[test]
+    fn writes_captures_promotions_and_checks() {
+        assert_eq!(san("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "h5f7"), "Qxf7#");
+        assert_eq!(san("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "c4f7"), "Bxf7+");
+        assert_eq!(san("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "e4d5"), "exd5");
+        assert_eq!(san("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), "e8=Q+");
+        assert_eq!(san("8/2k1P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8n"), "e8=N+");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"), "O-O-O");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8g8"), "O-O");
+    }
+
+    #[test]
+    fn every_legal_move_round_trips() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            let position = Position::read_fen(fen).unwrap();
+            for mv in position.legal_moves() {
+                let san = position.to_san(mv);
+                assert_eq!(position.parse_san(&san), Ok(mv), "{} in {}", san, fen);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::bitboard::Bitboard;
+use crate::magic::MagicAttacks;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use crate::movegen::rays;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use crate::pext::PextAttacks;
+use crate::rayattacks::Rays;
+
+// Attack lookups for bishops, rooks and queens given the occupied squares.
+// Every backend has to return exactly what the Rays implementation does.
+pub trait SliderAttacks {
+    fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard;
+    fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard;
+
+    fn queen_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        self.bishop_attacks(square, occupancy) | self.rook_attacks(square, occupancy)
+    }
+}
+
+impl SliderAttacks for Rays {
+    fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        Rays::bishop_attacks(self, square, occupancy)
+    }
+
+    fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        Rays::rook_attacks(self, square, occupancy)
+    }
+}
+
+impl SliderAttacks for MagicAttacks {
+    fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        MagicAttacks::bishop_attacks(self, square, occupancy)
+    }
+
+    fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        MagicAttacks::rook_attacks(self, square, occupancy)
+    }
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+impl SliderAttacks for PextAttacks {
+    fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        PextAttacks::bishop_attacks(self, square, occupancy)
+    }
+
+    fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        PextAttacks::rook_attacks(self, square, occupancy)
+    }
+}
+
+// The slider backend used by move generation, picked once at startup.
+// Building with `--features pext` uses pext lookups on CPUs with BMI2 and falls
+// back to the Rays implementation on the rest. Other builds use magic bitboards.
+pub enum Sliders {
+    Rays(&'static Rays),
+    Magic(Box<MagicAttacks>),
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    Pext(Box<PextAttacks>),
+}
+
+impl Sliders {
+    pub fn detect() -> Sliders {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        return match PextAttacks::init() {
+            Some(pext) => Sliders::Pext(Box::new(pext)),
+            None => Sliders::Rays(rays()),
+        };
+        #[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+        Sliders::Magic(Box::new(MagicAttacks::init()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sliders::Rays(_) => "rays",
+            Sliders::Magic(_) => "magic",
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            Sliders::Pext(_) => "pext",
+        }
+    }
+}
+
+impl SliderAttacks for Sliders {
+    #[inline]
+    fn bishop_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        match self {
+            Sliders::Rays(rays) => rays.bishop_attacks(square, occupancy),
+            Sliders::Magic(magics) => magics.bishop_attacks(square, occupancy),
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            Sliders::Pext(pext) => pext.bishop_attacks(square, occupancy),
+        }
+    }
+
+    #[inline]
+    fn rook_attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        match self {
+            Sliders::Rays(rays) => rays.rook_attacks(square, occupancy),
+            Sliders::Magic(magics) => magics.rook_attacks(square, occupancy),
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            Sliders::Pext(pext) => pext.rook_attacks(square, occupancy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::magic::Xorshift;
+    use crate::movegen::{rays, sliders};
+
+    // Every backend this build and CPU can run
+    fn backends() -> Vec<(&'static str, Box<dyn SliderAttacks>)> {
+        #[allow(unused_mut)]
+        let mut backends: Vec<(&'static str, Box<dyn SliderAttacks>)> = vec![
+            ("rays", Box::new(Sliders::Rays(rays()))),
+            ("magic", Box::new(MagicAttacks::init())),
+        ];
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if let Some(pext) = PextAttacks::init() {
+            backends.push(("pext", Box::new(pext)));
+        }
+        backends
+    }
+
+    #[test]
+    fn every_backend_matches_rays() {
+        let rays = rays();
+        for (name, backend) in backends() {
+            let mut random = Xorshift(0x9E37_79B9_7F4A_7C15);
+            for i in 0..300 {
+                // Dense, medium and sparse boards
+                let occupancy = match i % 3 {
+                    0 => Bitboard(random.next()),
+                    1 => Bitboard(random.next() & random.next()),
+                    _ => Bitboard(random.next() & random.next() & random.next()),
+                };
+                for square in 0..64 {
+                    assert_eq!(backend.bishop_attacks(square, occupancy), rays.bishop_attacks(square, occupancy), "{} bishop on {}", name, square);
+                    assert_eq!(backend.rook_attacks(square, occupancy), rays.rook_attacks(square, occupancy), "{} rook on {}", name, square);
+                    assert_eq!(backend.queen_attacks(square, occupancy), rays.queen_attacks(square, occupancy), "{} queen on {}", name, square);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn detects_the_expected_backend() {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        let expected = if std::is_x86_feature_detected!("bmi2") { "pext" } else { "rays" };
+        #[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+        let expected = "magic";
+        assert_eq!(sliders().name(), expected);
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bitboard::Bitboard;
+
+// A column of the board, a through h
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum File {
+    A, B, C, D, E, F, G, H,
+}
+
+// A row of the board, counted from white's side
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum Rank {
+    First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth,
+}
+
+// A square of the board. The discriminant is the square index used by the
+// bitboards: a1 = 0, h1 = 7, a8 = 56, h8 = 63
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+#[repr(u8)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    pub fn from_index(index: usize) -> Option<File> {
+        File::ALL.get(index).copied()
+    }
+
+    pub fn from_char(ch: char) -> Option<File> {
+        match ch {
+            'a'..='h' => File::from_index(ch as usize - 'a' as usize),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn to_char(&self) -> char {
+        (b'a' + *self as u8) as char
+    }
+
+    // Every square on this file
+    pub fn bitboard(&self) -> Bitboard {
+        Bitboard::FILES[self.index()]
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [Rank::First, Rank::Second, Rank::Third, Rank::Fourth, Rank::Fifth, Rank::Sixth, Rank::Seventh, Rank::Eighth];
+
+    pub fn from_index(index: usize) -> Option<Rank> {
+        Rank::ALL.get(index).copied()
+    }
+
+    pub fn from_char(ch: char) -> Option<Rank> {
+        match ch {
+            '1'..='8' => Rank::from_index(ch as usize - '1' as usize),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn to_char(&self) -> char {
+        (b'1' + *self as u8) as char
+    }
+
+    // Every square on this rank
+    pub fn bitboard(&self) -> Bitboard {
+        Bitboard::RANKS[self.index()]
+    }
+}
+
+impl Square {
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    pub fn new(file: File, rank: Rank) -> Square {
+        Square::ALL[rank.index() * 8 + file.index()]
+    }
+
+    pub fn from_index(index: usize) -> Option<Square> {
+        Square::ALL.get(index).copied()
+    }
+
+    // The square of a bitboard with exactly one bit set
+    pub fn from_bitboard(bitboard: Bitboard) -> Option<Square> {
+        if bitboard.popcount() == 1 {
+            bitboard.lsb()
+        } else {
+            None
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn file(&self) -> File {
+        File::ALL[self.index() % 8]
+    }
+
+    pub fn rank(&self) -> Rank {
+        Rank::ALL[self.index() / 8]
+    }
+
+    pub fn bitboard(&self) -> Bitboard {
+        Bitboard::from_index(self.index())
+    }
+
+    // The square `files` to the right and `ranks` up from this one, as seen by white.
+    // None when that would leave the board, so steps never wrap around an edge
+    pub fn offset(&self, files: i32, ranks: i32) -> Option<Square> {
+        let file = self.file().index() as i32 + files;
+        let rank = self.rank().index() as i32 + ranks;
+        if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+            return None;
+        }
+        Some(Square::new(File::ALL[file as usize], Rank::ALL[rank as usize]))
+    }
+
+    // The same square seen from black's side: a1 <-> a8
+    pub fn flip(&self) -> Square {
+        Square::ALL[self.index() ^ 56]
+    }
+
+    // The same square reflected across the d/e line: a1 <-> h1
+    pub fn mirror(&self) -> Square {
+        Square::ALL[self.index() ^ 7]
+    }
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> usize {
+        square.index()
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        square.bitboard()
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+// Why a string could not be read as a square
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseSquareError {
+    InvalidLength(String),
+    InvalidFile(char),
+    InvalidRank(char),
+}
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseSquareError::InvalidLength(s) => write!(f, "expected a square like e4, got '{}'", s),
+            ParseSquareError::InvalidFile(ch) => write!(f, "invalid file '{}'", ch),
+            ParseSquareError::InvalidRank(ch) => write!(f, "invalid rank '{}'", ch),
+        }
+    }
+}
+
+impl std::error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Square, ParseSquareError> {
+        let mut chars = s.chars();
+        let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(ParseSquareError::InvalidLength(s.to_string()));
+        };
+        let file = File::from_char(file).ok_or(ParseSquareError::InvalidFile(file))?;
+        let rank = Rank::from_char(rank).ok_or(ParseSquareError::InvalidRank(rank))?;
+        Ok(Square::new(file, rank))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares_round_trip_through_strings() {
+        for square in Square::ALL {
+            assert_eq!(square.to_string().parse::<Square>(), Ok(square));
+            assert_eq!(Square::from_index(square.index()), Some(square));
+            assert_eq!(Square::from_bitboard(square.bitboard()), Some(square));
+        }
+        assert_eq!(Square::E4.to_string(), "e4");
+        assert_eq!("h8".parse::<Square>(), Ok(Square::H8));
+        assert_eq!("i1".parse::<Square>(), Err(ParseSquareError::InvalidFile('i')));
+        assert_eq!("a9".parse::<Square>(), Err(ParseSquareError::InvalidRank('9')));
+        assert_eq!("e".parse::<Square>(), Err(ParseSquareError::InvalidLength("e".to_string())));
+        assert_eq!("e44".parse::<Square>(), Err(ParseSquareError::InvalidLength("e44".to_string())));
+        assert_eq!(Square::from_index(64), None);
+        assert_eq!(Square::from_bitboard(Bitboard(0b11)), None);
+    }
+
+    #[test]
+    fn files_and_ranks() {
+        assert_eq!(Square::C6.file(), File::C);
+        assert_eq!(Square::C6.rank(), Rank::Sixth);
+        assert_eq!(Square::new(File::G, Rank::Second), Square::G2);
+        assert_eq!(File::A.bitboard() & Rank::First.bitboard(), Square::A1.bitboard());
+        assert_eq!(File::H.bitboard() & Rank::Eighth.bitboard(), Square::H8.bitboard());
+        for square in Square::ALL {
+            assert!(square.file().bitboard().contains(square));
+            assert!(square.rank().bitboard().contains(square));
+        }
+    }
+
+    #[test]
+    fn offsets_stop_at_the_edge() {
+        assert_eq!(Square::E4.offset(1, 2), Some(Square::F6));
+        assert_eq!(Square::E4.offset(-4, -3), Some(Square::A1));
+        assert_eq!(Square::A4.offset(-1, 0), None);
+        assert_eq!(Square::H4.offset(1, 0), None);
+        assert_eq!(Square::H8.offset(0, 1), None);
+        assert_eq!(Square::A1.offset(0, -1), None);
+    }
+
+    #[test]
+    fn flip_and_mirror() {
+        assert_eq!(Square::A1.flip(), Square::A8);
+        assert_eq!(Square::E2.flip(), Square::E7);
+        assert_eq!(Square::A1.mirror(), Square::H1);
+        assert_eq!(Square::C5.mirror(), Square::F5);
+        for square in Square::ALL {
+            assert_eq!(square.flip().flip(), square);
+            assert_eq!(square.mirror().mirror(), square);
+        }
+    }
+}
@@ -1,39 +1,35 @@
-// Magic Table to enable fast implementation of a bit scan function
-type Bitboard = u64;
-
-static MOD67TABLE: [usize; 67] = [
-    64, 0, 1, 39, 2, 15, 40, 23,
-    3, 12, 16, 59, 41, 19, 24, 54,
-    4, 64, 13, 10, 17, 62, 60, 28,
-    42, 30, 20, 51, 25, 44, 55, 47,
-    5, 32, 64, 38, 14, 22, 11, 58,
-    18, 53, 63, 9, 61, 27, 29, 50,
-    43, 46, 31, 37, 21, 57, 52, 8,
-    26, 49, 45, 36, 56, 7, 48, 35,
-    6, 34, 33];
-
-// turns the first non-zero bit in a u64
-pub fn bit_scan(bit: u64) -> usize {
-    let one_bit = (bit ^ (bit - 1)) ^ (!bit & (bit - 1));
-    let remainder = (one_bit % 67) as usize;
-    return MOD67TABLE[remainder];
-} 
-
-pub fn bit_scan_backwards(bit: u64) -> usize {
-    // 0010000 = 16 = 2^4 index of 1 = ln( 0010000 ) = 4
-    // 0010010 = 18 = 2^4.2.. -> ln(0010010) = 4.2
-    (bit as f64).log2().floor() as usize
-}
+use crate::bitboard::Bitboard;
+use crate::square::Square;
 
+// Index of the lowest set bit, None for an empty bitboard.
+// Compiles down to a single tzcnt/bsf instruction on x86-64.
+pub fn bit_scan(bit: u64) -> Option<usize> {
+    if bit == 0 {
+        None
+    } else {
+        Some(bit.trailing_zeros() as usize)
+    }
+}
 
-pub fn set_bit (row: i32, col: i32) -> Bitboard {
-    if row < 1 || row > 8 || col < 1 || col > 8 {
-        return 0;
+// Index of the highest set bit, None for an empty bitboard
+pub fn bit_scan_backwards(bit: u64) -> Option<usize> {
+    if bit == 0 {
+        None
     } else {
-        return 1 << ((col - 1) + (row - 1) * 8);
+        Some(63 - bit.leading_zeros() as usize)
     }
 }
 
+// Number of set bits
+pub fn popcount(bit: u64) -> u32 {
+    bit.count_ones()
+}
+
+// The bit for the square on 1-based `row` and `col`, or an empty bitboard off the board
+pub fn set_bit (row: i32, col: i32) -> Bitboard {
+    Square::A1.offset(col - 1, row - 1).map_or(Bitboard::EMPTY, |square| square.bitboard())
+}
+
 pub fn print_bitboard (bitboard: Bitboard, mark:Option<usize>) {
     println!("{}", bitboard_to_string(bitboard, mark))
 }
@@ -43,7 +39,7 @@ pub fn bitboard_to_string (bitboard: Bitboard, mark:Option<usize>) -> String {
     let mut board  = "".to_owned();
 
     for i in 0..64 {
-        let value = (bitboard >> i) & 1;
+        let value = (bitboard.0 >> i) & 1;
         let s = if value == 0 {
             ".".to_owned()
         } else {
@@ -53,7 +49,7 @@ pub fn bitboard_to_string (bitboard: Bitboard, mark:Option<usize>) -> String {
         match mark {
             Some(idx) => {
                 if i == idx {
-                    row.push_str("X");
+                    row.push('X');
                 }else {
                     row.push_str(&s);
                 }
@@ -62,17 +58,107 @@ pub fn bitboard_to_string (bitboard: Bitboard, mark:Option<usize>) -> String {
         }
 
         if (i + 1) % 8 == 0 {
-            row.push_str("\n");
+            row.push('\n');
             board.insert_str(0, &row);
             row.clear();
         }
     }
-    return board
+    board
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    // The table based scans these functions replaced, kept to check and time the new ones against
+    static MOD67TABLE: [usize; 67] = [
+        64, 0, 1, 39, 2, 15, 40, 23,
+        3, 12, 16, 59, 41, 19, 24, 54,
+        4, 64, 13, 10, 17, 62, 60, 28,
+        42, 30, 20, 51, 25, 44, 55, 47,
+        5, 32, 64, 38, 14, 22, 11, 58,
+        18, 53, 63, 9, 61, 27, 29, 50,
+        43, 46, 31, 37, 21, 57, 52, 8,
+        26, 49, 45, 36, 56, 7, 48, 35,
+        6, 34, 33];
+
+    fn mod67_bit_scan(bit: u64) -> usize {
+        let one_bit = (bit ^ (bit - 1)) ^ (!bit & (bit - 1));
+        let remainder = (one_bit % 67) as usize;
+        MOD67TABLE[remainder]
+    }
+
+    fn log2_bit_scan_backwards(bit: u64) -> usize {
+        (bit as f64).log2().floor() as usize
+    }
+
+    fn kernighan_popcount(mut bit: u64) -> u32 {
+        let mut count = 0;
+        while bit != 0 {
+            bit &= bit - 1;
+            count += 1;
+        }
+        count
+    }
+
+    // Pseudo random bitboards with a mix of sparse and dense ones, never empty
+    fn sample_bitboards(count: usize) -> Vec<u64> {
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        (0..count).map(|i| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let bit = if i % 2 == 0 { state & state.rotate_left(19) } else { state };
+            bit | 1 << (i % 64)
+        }).collect()
+    }
+
+    fn time<T>(name: &str, samples: &[u64], scan: impl Fn(u64) -> T) {
+        let start = Instant::now();
+        for _ in 0..100 {
+            for &bit in samples {
+                black_box(scan(black_box(bit)));
+            }
+        }
+        let elapsed = start.elapsed();
+        println!("{:<28} {:>10.2?} ({:.2} ns/scan)", name, elapsed, elapsed.as_nanos() as f64 / (samples.len() * 100) as f64);
+    }
+
+    #[test]
+    fn empty_bitboards_have_no_bits() {
+        assert_eq!(bit_scan(0), None);
+        assert_eq!(bit_scan_backwards(0), None);
+        assert_eq!(popcount(0), 0);
+        assert_eq!(bit_scan_backwards(u64::MAX), Some(63));
+        assert_eq!(popcount(u64::MAX), 64);
+    }
+
+    #[test]
+    fn matches_table_based_scans() {
+        for bit in sample_bitboards(10_000) {
+            assert_eq!(bit_scan(bit), Some(mod67_bit_scan(bit)));
+            assert_eq!(popcount(bit), kernighan_popcount(bit));
+            // log2 rounds 2^64 - 1 and friends up, so the old backward scan is only exact below 2^53
+            if bit < 1 << 53 {
+                assert_eq!(bit_scan_backwards(bit), Some(log2_bit_scan_backwards(bit)));
+            }
+        }
+    }
+
+    // cargo test --release --lib bit_scan_benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore = "benchmark, run with --release -- --ignored --nocapture"]
+    fn bit_scan_benchmark() {
+        let samples = sample_bitboards(100_000);
+        time("bit_scan (mod 67 table)", &samples, mod67_bit_scan);
+        time("bit_scan", &samples, bit_scan);
+        time("bit_scan_backwards (log2)", &samples, log2_bit_scan_backwards);
+        time("bit_scan_backwards", &samples, bit_scan_backwards);
+        time("popcount (loop)", &samples, kernighan_popcount);
+        time("popcount", &samples, popcount);
+    }
+
     #[test]
     fn test_bit_scan_backwards(){
         for highest_bit in 0..64 {
@@ -83,16 +169,16 @@ mod tests {
                 }
             }
             let bit_scan_result = bit_scan_backwards(bit);
-            assert_eq!(highest_bit, bit_scan_result);
+            assert_eq!(Some(highest_bit), bit_scan_result);
         }
     }
 
     #[test]
     fn bit_scan_works(){
         for i in 0..64 {
-            let bit = (1 as u64) << i;
+            let bit = 1_u64 << i;
             let index = bit_scan(bit);
-            assert_eq!(i, index);
+            assert_eq!(Some(i), index);
         }
     }
 
@@ -108,17 +194,17 @@ mod tests {
             }
 
             let bit_scan_result = bit_scan(bit);
-            assert_eq!(lowest_bit, bit_scan_result);
+            assert_eq!(Some(lowest_bit), bit_scan_result);
         }
     }
 
     #[test]
     fn bit_scan_works_highest_bit_is_1(){
         for i in 0..64 {
-            let mut bit = (1 as u64) << i;
-            bit |= (1 as u64) << 63;
+            let mut bit = 1_u64 << i;
+            bit |= 1_u64 << 63;
             let index = bit_scan(bit);
-            assert_eq!(i, index);
+            assert_eq!(Some(i), index);
         }
     }
 }
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use crate::bitboard::Bitboard;
+use crate::game::*;
+use crate::position::Position;
+
+const BACK_RANKS: Bitboard = Bitboard(Bitboard::RANK_1.0 | Bitboard::RANK_8.0);
+
+// Something that makes a parsed position impossible in a real game of chess
+#[derive(Debug, PartialEq, Clone)]
+pub enum PositionProblem {
+    WrongKingCount { color: Color, count: u32 },
+    PawnOnBackRank { square: usize },
+    // The side that just moved left its own king in check
+    OpponentInCheck { color: Color },
+    // A castling right whose king or rook is not on its starting square
+    CastlingRightWithoutPieces { right: CastlingRights },
+    // An en passant square that no double pawn push could have produced
+    InvalidEnPassant { square: usize },
+}
+
+impl fmt::Display for PositionProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionProblem::WrongKingCount { color, count } =>
+                write!(f, "{:?} has {} kings instead of 1", color, count),
+            PositionProblem::PawnOnBackRank { square } =>
+                write!(f, "pawn on back rank square {}", index_to_position(*square)),
+            PositionProblem::OpponentInCheck { color } =>
+                write!(f, "{:?} is in check but not to move", color),
+            PositionProblem::CastlingRightWithoutPieces { right } =>
+                write!(f, "castling right {:?} without king and rook on their starting squares", right),
+            PositionProblem::InvalidEnPassant { square } =>
+                write!(f, "en passant square {} does not follow a double pawn push", index_to_position(*square)),
+        }
+    }
+}
+
+// King and rook starting squares for each castling right
+const CASTLING_SQUARES: [(CastlingRights, Color, usize, usize); 4] = [
+    (CastlingRights::WHITEKINGSIDE, Color::White, 4, 7),
+    (CastlingRights::WHITEQUEENSIDE, Color::White, 4, 0),
+    (CastlingRights::BLACKKINGSIDE, Color::Black, 60, 63),
+    (CastlingRights::BLACKQUEENSIDE, Color::Black, 60, 56),
+];
+
+impl Position {
+    // Checks the position for things that cannot happen in a legal game.
+    // An empty list means the position is fine.
+    pub fn validate(&self) -> Vec<PositionProblem> {
+        let mut problems = vec![];
+
+        for color in [Color::White, Color::Black] {
+            let count = self.piece_bitboard(color, PieceType::King).popcount();
+            if count != 1 {
+                problems.push(PositionProblem::WrongKingCount { color, count });
+            }
+        }
+
+        let back_rank_pawns = (self.piece_bitboard(Color::White, PieceType::Pawn)
+            | self.piece_bitboard(Color::Black, PieceType::Pawn)) & BACK_RANKS;
+        for square in back_rank_pawns {
+            problems.push(PositionProblem::PawnOnBackRank { square: square.index() });
+        }
+
+        let waiting = self.active_color.opposite();
+        let king = self.piece_bitboard(waiting, PieceType::King);
+        if let Some(square) = king.lsb()
+            && king.popcount() == 1
+            && !self.attackers(square.index(), self.active_color, self.occupancy()).is_empty() {
+            problems.push(PositionProblem::OpponentInCheck { color: waiting });
+        }
+
+        for (right, color, king_square, rook_square) in CASTLING_SQUARES {
+            if !self.castling_rights.contains(right) {
+                continue;
+            }
+            let in_place = |square: usize, piece_type: PieceType| self.piece_at(square) == Some((color, piece_type));
+            if !in_place(king_square, PieceType::King) || !in_place(rook_square, PieceType::Rook) {
+                problems.push(PositionProblem::CastlingRightWithoutPieces { right });
+            }
+        }
+
+        if let Some(square) = self.en_passant {
+            let square = square.index();
+            if !self.is_possible_en_passant(square) {
+                problems.push(PositionProblem::InvalidEnPassant { square });
+            }
+        }
+
+        problems
+    }
+
+    // The square must be the one skipped by a pawn of the side not to move that
+    // just pushed two squares: empty, with its start square empty and the pawn in front.
+    pub(crate) fn is_possible_en_passant(&self, square: usize) -> bool {
+        let (rank, pawn_square, start_square) = match self.active_color {
+            Color::White => (5, square.wrapping_sub(8), square + 8),
+            Color::Black => (2, square + 8, square.wrapping_sub(8)),
+        };
+        if square / 8 != rank {
+            return false;
+        }
+        let pawn_pushed = self.piece_at(pawn_square) == Some((self.active_color.opposite(), PieceType::Pawn));
+        pawn_pushed && self.piece_at(square).is_none() && self.piece_at(start_square).is_none()
+    }
+}
+
+impl Game {
+    pub fn validate(&self) -> Vec<PositionProblem> {
+        self.position.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(fen: &str) -> Vec<PositionProblem> {
+        Position::read_fen(fen).unwrap().validate()
+    }
+
+    #[test]
+    fn legal_positions_have_no_problems() {
+        assert!(problems("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_empty());
+        assert!(problems("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").is_empty());
+        assert!(problems("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").is_empty());
+        assert!(problems("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1").is_empty());
+    }
+
+    #[test]
+    fn finds_each_problem() {
+        assert_eq!(problems("8/8/8/8/8/8/8/K1K4k w - - 0 1"),
+            vec![PositionProblem::WrongKingCount { color: Color::White, count: 2 }]);
+        assert_eq!(problems("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+            vec![PositionProblem::WrongKingCount { color: Color::White, count: 0 }]);
+        assert_eq!(problems("4k2P/8/8/8/8/8/8/p3K3 w - - 0 1"),
+            vec![PositionProblem::PawnOnBackRank { square: 0 }, PositionProblem::PawnOnBackRank { square: 63 }]);
+        assert_eq!(problems("4k3/8/8/8/8/8/8/4K2r b - - 0 1"),
+            vec![PositionProblem::OpponentInCheck { color: Color::White }]);
+        assert_eq!(problems("4k3/8/8/8/8/8/8/R3K3 w KQ - 0 1"),
+            vec![PositionProblem::CastlingRightWithoutPieces { right: CastlingRights::WHITEKINGSIDE }]);
+        assert_eq!(problems("4k3/8/8/8/8/8/8/4K3 w - e6 0 1"),
+            vec![PositionProblem::InvalidEnPassant { square: 44 }]);
+        assert_eq!(problems("4k3/8/8/4p3/8/8/8/4K3 w - e3 0 1"),
+            vec![PositionProblem::InvalidEnPassant { square: 20 }]);
+    }
+}
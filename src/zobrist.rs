@@ -0,0 +1,260 @@
+use crate::game::*;
+use crate::position::Position;
+use crate::square::Square;
+
+// Random keys for Zobrist hashing. A position's key is the xor of the keys of its pieces,
+// castling rights and en passant file, plus BLACK_TO_MOVE when it is black's turn, so
+// making a move only has to xor out what changed and xor in what replaced it.
+// Built at compile time from a fixed seed, so keys are the same in every build.
+pub struct ZobristKeys {
+    // Indexed by Color::index, PieceType::index and square
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    // Indexed by CastlingRights::bits
+    castling: [u64; 16],
+    en_passant_files: [u64; 8],
+}
+
+pub static ZOBRIST: ZobristKeys = generate_keys(0x9E37_79B9_7F4A_7C15);
+
+// splitmix64, which spreads even a simple counter into well mixed 64 bit values
+const fn next_key(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys(seed: u64) -> ZobristKeys {
+    let mut state = seed;
+    let mut pieces = [[[0; 64]; 6]; 2];
+    let mut color = 0;
+    while color < 2 {
+        let mut piece_type = 0;
+        while piece_type < 6 {
+            let mut square = 0;
+            while square < 64 {
+                pieces[color][piece_type][square] = next_key(&mut state);
+                square += 1;
+            }
+            piece_type += 1;
+        }
+        color += 1;
+    }
+
+    let black_to_move = next_key(&mut state);
+
+    // No rights hashes to 0, like an empty board
+    let mut castling = [0; 16];
+    let mut rights = 1;
+    while rights < 16 {
+        castling[rights] = next_key(&mut state);
+        rights += 1;
+    }
+
+    let mut en_passant_files = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        en_passant_files[file] = next_key(&mut state);
+        file += 1;
+    }
+
+    ZobristKeys { pieces, black_to_move, castling, en_passant_files }
+}
+
+impl ZobristKeys {
+    pub fn piece(&self, color: Color, piece_type: PieceType, square: usize) -> u64 {
+        self.pieces[color.index()][piece_type.index()][square]
+    }
+
+    pub fn black_to_move(&self) -> u64 {
+        self.black_to_move
+    }
+
+    pub fn castling(&self, rights: CastlingRights) -> u64 {
+        self.castling[rights.bits() as usize]
+    }
+
+    // 0 when there is no en passant square
+    pub fn en_passant(&self, en_passant: Option<Square>) -> u64 {
+        match en_passant {
+            Some(square) => self.en_passant_files[square.file().index()],
+            None => 0,
+        }
+    }
+}
+
+impl Position {
+    // The Zobrist key, kept up to date by read_fen, make_move and the setters
+    pub fn zobrist_key(&self) -> u64 {
+        self.key
+    }
+
+    // The Zobrist key worked out from scratch
+    pub fn compute_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+        for color in [Color::White, Color::Black] {
+            for piece_type in PieceType::ALL {
+                for square in self.piece_bitboard(color, piece_type) {
+                    key ^= ZOBRIST.piece(color, piece_type, square.index());
+                }
+            }
+        }
+        if self.active_color == Color::Black {
+            key ^= ZOBRIST.black_to_move();
+        }
+        key ^ ZOBRIST.castling(self.castling_rights) ^ ZOBRIST.en_passant(self.hashed_en_passant())
+    }
+
+    // Recomputes the key after the fields were changed directly
+    pub(crate) fn refresh_zobrist_key(&mut self) {
+        self.key = self.compute_zobrist_key();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitboard;
+    use crate::moves::Move;
+
+    fn play(fen: &str, moves: &[&str]) -> Position {
+        let mut position = Position::read_fen(fen).unwrap();
+        for &uci in moves {
+            let mv = position.legal_moves().into_iter().find(|mv| mv.to_string() == uci).unwrap();
+            position.make_move(mv);
+        }
+        position
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn transpositions_share_a_key() {
+        let one = play(START, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let other = play(START, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+        assert_eq!(one.zobrist_key(), other.zobrist_key());
+        assert_eq!(one.zobrist_key(), Position::read_fen(&one.to_fen()).unwrap().zobrist_key());
+
+        // Knights going out and back leave the same position with the same key
+        let back = play(START, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(back.zobrist_key(), Position::read_fen(START).unwrap().zobrist_key());
+    }
+
+    #[test]
+    fn en_passant_only_counts_when_a_capture_is_possible() {
+        // After 1. e4 no black pawn can take on e3, so the position is the same as without the square
+        let pushed = play(START, &["e2e4"]);
+        let without = Position::read_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let with = Position::read_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        assert_eq!(pushed.en_passant(), Some(Square::E3));
+        assert_eq!(pushed.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(pushed.to_fen(), with.to_fen());
+        assert_eq!(pushed.zobrist_key(), without.zobrist_key());
+        assert_eq!(with.zobrist_key(), without.zobrist_key());
+        assert_eq!(pushed, without);
+        let mut seen = std::collections::HashSet::new();
+        seen.insert(without);
+        assert!(!seen.insert(pushed));
+
+        // With a black pawn on d4 the capture is on, and the square is part of the key
+        let capturable = play("rnbqkbnr/ppp1pppp/8/8/3p4/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["e2e4"]);
+        assert_eq!(capturable.to_fen(), "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let plain = Position::read_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        assert_ne!(capturable.zobrist_key(), plain.zobrist_key());
+        assert_ne!(capturable, plain);
+    }
+
+    #[test]
+    fn every_part_of_the_position_changes_the_key() {
+        let key = |fen| Position::read_fen(fen).unwrap().zobrist_key();
+        let base = key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        let others = [
+            key("r3k2r/8/8/3pP3/8/8/8/R3K2R b KQkq d6 0 1"),
+            key("r3k2r/8/8/3pP3/8/8/8/R3K2R w Qkq d6 0 1"),
+            key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq - 0 1"),
+            key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq f6 0 1"),
+            key("r3k2r/8/8/3pP3/8/8/8/R2K3R w kq d6 0 1"),
+        ];
+        for other in others {
+            assert_ne!(other, base);
+        }
+        // The move counters are not part of the key
+        assert_eq!(key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 12 40"), base);
+        assert_eq!(Position::empty().zobrist_key(), Position::empty().compute_zobrist_key());
+    }
+
+    // Walks every line a few plies deep, comparing the incremental key with a fresh one
+    fn check_keys(position: &Position, depth: usize) {
+        if depth == 0 {
+            return;
+        }
+        for mv in position.legal_moves() {
+            let mut after = *position;
+            after.make_move(mv);
+            assert_eq!(after.zobrist_key(), after.compute_zobrist_key(), "{} after {}", position.to_fen(), mv);
+            check_keys(&after, depth - 1);
+        }
+    }
+
+    #[test]
+    fn incremental_keys_match_computed_keys() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            check_keys(&Position::read_fen(fen).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn unmake_restores_the_key() {
+        let mut game = Game::read_fen(START).unwrap();
+        let start = game.position.zobrist_key();
+        let moves: Vec<Move> = game.legal_moves();
+        for mv in moves {
+            game.make_move(mv);
+            assert_ne!(game.position.zobrist_key(), start);
+            game.unmake_move();
+            assert_eq!(game.position.zobrist_key(), start);
+        }
+    }
+
+    #[test]
+    fn setters_keep_the_key_in_step() {
+        let mut position = Position::read_fen(START).unwrap();
+        position.set_castling_rights(CastlingRights::NONE);
+        assert_eq!(position.zobrist_key(), Position::read_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1").unwrap().zobrist_key());
+
+        position.remove_piece(12);
+        position.put_piece(36, Color::White, PieceType::Pawn);
+        position.remove_piece(51);
+        position.put_piece(35, Color::Black, PieceType::Pawn);
+        position.set_en_passant(Some(Square::D6));
+        assert_eq!(position.en_passant(), Some(Square::D6));
+        assert_eq!(position.to_fen(), "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w - d6 0 1");
+        assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+
+        // Putting a piece on an occupied square replaces it in the mailbox and the bitboards
+        assert_eq!(position.put_piece(3, Color::White, PieceType::King), Some((Color::White, PieceType::Queen)));
+        assert_eq!(position.put_piece(3, Color::White, PieceType::Queen), Some((Color::White, PieceType::King)));
+        assert_eq!(position.piece_bitboard(Color::White, PieceType::King), Bitboard::from_index(4));
+        assert_eq!(position.piece_bitboard(Color::White, PieceType::Queen), Bitboard::from_index(3));
+        assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+
+        // Each setter only changes its own field, whatever order they are called in
+        position.set_active_color(Color::Black);
+        assert_eq!(position.en_passant(), Some(Square::D6));
+        assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+        position.set_en_passant(None);
+        assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+
+        // make_move checks the key in debug builds, so this would panic on a stale one
+        let mv = position.legal_moves()[0];
+        position.make_move(mv);
+        assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+    }
+}
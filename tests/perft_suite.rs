@@ -0,0 +1,196 @@
+// Perft regression suite driven by the reference counts in tests/perft.epd.
+//
+// `cargo test` checks every depth whose expected count is small enough to run quickly
+// in a debug build. The full suite is opt-in:
+//
+//     cargo test --release --test perft_suite -- --ignored
+
+use rust_chess_engine::game::{Game, PieceType};
+use rust_chess_engine::moves::Move;
+use rust_chess_engine::perft::{divide, perft};
+use rust_chess_engine::position::Position;
+use rust_chess_engine::square::Square;
+
+const PERFT_EPD: &str = include_str!("perft.epd");
+
+// Depths expecting more nodes than this are left to the ignored deep test
+const SHALLOW_NODE_LIMIT: u64 = 100_000;
+
+struct PerftCase {
+    line: usize,
+    fen: String,
+    expected: Vec<(usize, u64)>,
+}
+
+fn parse_epd(contents: &str) -> Vec<PerftCase> {
+    let mut cases = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(';');
+        let fen = fields.next().unwrap_or_default().trim().to_string();
+        let mut expected = vec![];
+        for field in fields {
+            let (depth, nodes) = field.trim().split_once(' ')
+                .unwrap_or_else(|| panic!("line {}: malformed field '{}'", index + 1, field));
+            let depth = depth.strip_prefix('D')
+                .and_then(|depth| depth.parse().ok())
+                .unwrap_or_else(|| panic!("line {}: invalid depth '{}'", index + 1, depth));
+            let nodes = nodes.trim().parse()
+                .unwrap_or_else(|_| panic!("line {}: invalid node count '{}'", index + 1, nodes));
+            expected.push((depth, nodes));
+        }
+        expected.sort();
+        cases.push(PerftCase { line: index + 1, fen, expected });
+    }
+    cases
+}
+
+// Whether `mv` keeps the mover's king safe, found by playing it out. Deliberately slow and
+// independent of the pin and check masks legal_moves uses, so it can referee them.
+fn is_legal_by_playing(position: &Position, mv: Move) -> bool {
+    let us = position.active_color();
+    if mv.is_castling() {
+        let crossed = Square::from_index((mv.from() + mv.to()) / 2).unwrap();
+        if position.in_check() || position.is_square_attacked(crossed, us.opposite()) {
+            return false;
+        }
+    }
+    let mut after = *position;
+    after.make_move(mv);
+    after.piece_bitboard(us, PieceType::King).lsb()
+        .is_none_or(|king| !after.is_square_attacked(king, us.opposite()))
+}
+
+fn reference_perft(position: &Position, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    for mv in position.pseudo_legal_moves() {
+        if is_legal_by_playing(position, mv) {
+            let mut after = *position;
+            after.make_move(mv);
+            nodes += reference_perft(&after, depth - 1);
+        }
+    }
+    nodes
+}
+
+// Follows the first root move whose count disagrees with the reference down the tree,
+// returning the moves leading to the divergence and the divide line where it shows.
+fn find_divergence(game: &mut Game, depth: usize) -> (Vec<Move>, String) {
+    let position = game.position;
+    let found = divide(game, depth);
+    for &(mv, nodes) in &found {
+        if !is_legal_by_playing(&position, mv) {
+            return (vec![], format!("{}: {} (illegal move generated)", mv, nodes));
+        }
+        let mut after = position;
+        after.make_move(mv);
+        let expected = reference_perft(&after, depth - 1);
+        if nodes != expected {
+            if depth == 1 {
+                return (vec![], format!("{}: {}, expected {}", mv, nodes, expected));
+            }
+            game.make_move(mv);
+            let (mut path, line) = find_divergence(game, depth - 1);
+            game.unmake_move();
+            path.insert(0, mv);
+            return (path, line);
+        }
+    }
+    let missing = position.pseudo_legal_moves().into_iter()
+        .find(|mv| is_legal_by_playing(&position, *mv) && !found.iter().any(|(generated, _)| generated == mv));
+    match missing {
+        Some(mv) => (vec![], format!("{}: missing from the generated moves", mv)),
+        None => (vec![], "every root move agrees with the reference".to_string()),
+    }
+}
+
+// Runs every depth of `case` expecting at most `node_limit` nodes, shallowest first.
+// On the first mismatch, returns a report with the line of play down to the first
+// divide entry that disagrees with the reference count.
+fn check_case(case: &PerftCase, node_limit: u64) -> Result<usize, String> {
+    let mut game = Game::read_fen(&case.fen)
+        .map_err(|err| format!("line {}: {}\ninvalid FEN: {}\n", case.line, case.fen, err))?;
+    let mut checked = 0;
+
+    for &(depth, expected) in case.expected.iter().filter(|(_, nodes)| *nodes <= node_limit) {
+        let found = perft(&mut game, depth);
+        if found == expected {
+            checked += 1;
+            continue;
+        }
+
+        let (path, line) = find_divergence(&mut game, depth);
+        let path: Vec<String> = path.iter().map(|mv| mv.to_string()).collect();
+        return Err(format!(
+            "line {}: {}\nfirst divergence at depth {}: expected {}, found {}\nafter [{}]: {}\n",
+            case.line, case.fen, depth, expected, found, path.join(" "), line
+        ));
+    }
+    Ok(checked)
+}
+
+fn run_suite(node_limit: u64) {
+    let cases = parse_epd(PERFT_EPD);
+    assert!(!cases.is_empty(), "no positions in perft.epd");
+
+    let mut checked = 0;
+    let mut failures = vec![];
+    for case in &cases {
+        match check_case(case, node_limit) {
+            Ok(depths) => checked += depths,
+            Err(report) => failures.push(report),
+        }
+    }
+
+    assert!(failures.is_empty(), "{} of {} positions failed:\n\n{}", failures.len(), cases.len(), failures.join("\n"));
+    assert!(checked > 0, "no depths were checked");
+}
+
+#[test]
+fn perft_suite_shallow() {
+    run_suite(SHALLOW_NODE_LIMIT);
+}
+
+#[test]
+#[ignore = "deep perft takes minutes, run with --release -- --ignored"]
+fn perft_suite_deep() {
+    run_suite(u64::MAX);
+}
+
+#[test]
+fn parses_epd_fields() {
+    let cases = parse_epd("# comment\n\n4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D2 66 ;D1 15\n");
+    assert_eq!(cases.len(), 1);
+    assert_eq!(cases[0].line, 3);
+    assert_eq!(cases[0].fen, "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(cases[0].expected, vec![(1, 15), (2, 66)]);
+}
+
+#[test]
+fn mismatch_reports_depth_and_divergent_line() {
+    let case = PerftCase {
+        line: 1,
+        fen: "4k3/8/8/8/8/8/8/4K2R w K - 0 1".to_string(),
+        expected: vec![(1, 15), (2, 67)],
+    };
+    let report = check_case(&case, u64::MAX).unwrap_err();
+    assert!(report.contains("depth 2: expected 67, found 66"));
+    // The generator is right here, so the reference agrees with every root move
+    assert!(report.ends_with("after []: every root move agrees with the reference\n"), "{}", report);
+    assert_eq!(report.lines().count(), 3);
+}
+
+#[test]
+fn reference_agrees_with_the_generator() {
+    let mut game = Game::read_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(reference_perft(&game.position, 2), perft(&mut game, 2));
+    let mut game = Game::read_fen("8/8/8/KP5r/8/8/8/7k w - - 0 1").unwrap();
+    assert_eq!(reference_perft(&game.position, 3), perft(&mut game, 3));
+}